@@ -0,0 +1,271 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use clap::{Args, Parser, Subcommand};
+use color_eyre::eyre::{bail, eyre, Error, Result};
+
+use crate::input::Source;
+use crate::output::Format;
+use crate::params::Assignment;
+use crate::runner;
+use crate::solution::Part;
+
+pub const LAST_DAY: u8 = 25;
+
+#[derive(Debug, Parser)]
+#[command(name = "aoc-2022-rust", about = "AoC 2022 solutions using Rust")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Solve one or more days
+    Run(RunArgs),
+    /// Check the answers of one or more days against a manifest of known answers
+    Verify(VerifyArgs),
+    /// Download the puzzle input of a day into the inputs directory, unless it is already there
+    Fetch(FetchArgs),
+    /// Solve one part of a day and submit its answer
+    Submit(SubmitArgs),
+    /// Create and register the module of a new day from the template
+    New(NewArgs),
+    /// Generate a random puzzle input for a day
+    Gen(GenArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct GenArgs {
+    #[arg(value_parser = clap::value_parser!(u8).range(1..=LAST_DAY as i64))]
+    pub day: u8,
+
+    /// Seed of the generator; the same seed always gives the same input. Random when omitted
+    #[arg(short, long)]
+    pub seed: Option<u64>,
+
+    /// Size of the input, in the day's own unit (lines, monkeys, jets...); defaults to about
+    /// that of the real puzzle input
+    #[arg(long)]
+    pub size: Option<usize>,
+
+    /// Write the input to this file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct NewArgs {
+    #[arg(value_parser = clap::value_parser!(u8).range(1..=LAST_DAY as i64))]
+    pub day: u8,
+
+    /// Root of the crate the day is added to
+    #[arg(long, default_value = ".")]
+    pub root: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct SubmitArgs {
+    #[arg(value_parser = clap::value_parser!(u8).range(1..=LAST_DAY as i64))]
+    pub day: u8,
+
+    #[arg(value_parser = clap::value_parser!(u8).range(1..=2))]
+    pub part: u8,
+
+    /// Solve this input file instead, `-` for stdin
+    #[arg(short, long)]
+    pub input: Option<PathBuf>,
+
+    /// Directory holding `dayNN.txt` inputs; a day without a file falls back to the embedded input
+    #[arg(long, default_value = "inputs")]
+    pub inputs_dir: PathBuf,
+
+    /// Config file holding the session token and server settings
+    #[arg(short, long, default_value = "aoc.toml")]
+    pub config: PathBuf,
+
+    /// Log of every submitted answer, used to never submit the same wrong answer twice
+    #[arg(long, default_value = "submissions.json")]
+    pub log: PathBuf,
+}
+
+impl SubmitArgs {
+    pub fn source(&self) -> Source {
+        Source::from_args(self.input.clone(), self.inputs_dir.clone())
+    }
+
+    pub fn part(&self) -> Part {
+        if self.part == 1 {
+            Part::One
+        } else {
+            Part::Two
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct FetchArgs {
+    #[arg(value_parser = clap::value_parser!(u8).range(1..=LAST_DAY as i64))]
+    pub day: u8,
+
+    /// Directory the input is saved to as `dayNN.txt`
+    #[arg(long, default_value = "inputs")]
+    pub inputs_dir: PathBuf,
+
+    /// Config file holding the session token and server settings
+    #[arg(short, long, default_value = "aoc.toml")]
+    pub config: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct VerifyArgs {
+    /// Days to verify, same syntax as for `run`
+    #[arg(default_value = "all")]
+    pub days: DaySelection,
+
+    /// Manifest with one entry of known answers per input file
+    #[arg(short, long, default_value = "answers.toml")]
+    pub manifest: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct RunArgs {
+    /// Days to run: `all`, a single day (`5`), a range (`3-8`) or a comma separated list of those
+    pub days: DaySelection,
+
+    /// Only run the given part
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    pub part: Option<u8>,
+
+    /// Read the input from this file instead, `-` for stdin. Only valid for a single day
+    #[arg(short, long)]
+    pub input: Option<PathBuf>,
+
+    /// Directory holding `dayNN.txt` inputs; days without a file fall back to the embedded input
+    #[arg(long, default_value = "inputs")]
+    pub inputs_dir: PathBuf,
+
+    /// Print a timing summary of parsing and each part after the answers
+    #[arg(short, long)]
+    pub time: bool,
+
+    /// Print the events solvers emit while they run, such as finished rounds, to stderr
+    #[arg(long)]
+    pub trace: bool,
+
+    /// Solve every day this many times; the timing summary reports min, median and mean
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub iterations: u32,
+
+    /// Number of days solved in parallel; results are still reported in day order
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub jobs: u32,
+
+    /// Output format; `json` and `csv` emit one record per day and part
+    #[arg(short, long, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+
+    /// Override a puzzle parameter, e.g. `day11.part2_rounds=30`; takes precedence over the
+    /// `[params.dayNN]` tables of the config file. Can be repeated
+    #[arg(long = "param", value_name = "DAY.NAME=VALUE")]
+    pub params: Vec<Assignment>,
+
+    /// Config file holding parameter overrides
+    #[arg(short, long, default_value = "aoc.toml")]
+    pub config: PathBuf,
+}
+
+impl RunArgs {
+    pub fn source(&self) -> Source {
+        Source::from_args(self.input.clone(), self.inputs_dir.clone())
+    }
+
+    pub fn parts(&self) -> Vec<Part> {
+        match self.part {
+            Some(1) => vec![Part::One],
+            Some(_) => vec![Part::Two],
+            None => Part::ALL.to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DaySelection {
+    All,
+    Days(Vec<u8>),
+}
+
+impl DaySelection {
+    /// Selected day numbers, `all` meaning every implemented day.
+    pub fn days(&self) -> Vec<u8> {
+        match self {
+            DaySelection::All => runner::DAYS.iter().map(|d| d.day).collect(),
+            DaySelection::Days(days) => days.clone(),
+        }
+    }
+}
+
+fn parse_day(s: &str) -> Result<u8> {
+    let day = s
+        .trim()
+        .parse::<u8>()
+        .map_err(|_| eyre!("not a day: {s:?}"))?;
+    if !(1..=LAST_DAY).contains(&day) {
+        bail!("unknown day {day}, expected 1-{LAST_DAY}");
+    }
+    Ok(day)
+}
+
+impl FromStr for DaySelection {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s.eq_ignore_ascii_case("all") {
+            return Ok(DaySelection::All);
+        }
+        let mut days = Vec::<u8>::new();
+        for item in s.split(',') {
+            match item.split_once('-') {
+                Some((start, end)) => {
+                    let (start, end) = (parse_day(start)?, parse_day(end)?);
+                    if start > end {
+                        bail!("empty day range {item:?}");
+                    }
+                    days.extend(start..=end);
+                }
+                None => days.push(parse_day(item)?),
+            }
+        }
+        days.sort_unstable();
+        days.dedup();
+        Ok(DaySelection::Days(days))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn day_selection_parses_single_days_ranges_and_lists() {
+        assert_eq!("all".parse::<DaySelection>().unwrap(), DaySelection::All);
+        assert_eq!(
+            "5".parse::<DaySelection>().unwrap(),
+            DaySelection::Days(vec![5])
+        );
+        assert_eq!(
+            "8,3-5,4".parse::<DaySelection>().unwrap(),
+            DaySelection::Days(vec![3, 4, 5, 8])
+        );
+    }
+
+    #[test]
+    fn day_selection_rejects_unknown_days() {
+        assert!("0".parse::<DaySelection>().is_err());
+        assert!("26".parse::<DaySelection>().is_err());
+        assert!("6-2".parse::<DaySelection>().is_err());
+        assert!("five".parse::<DaySelection>().is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use std::fs;
+use std::path::Path;
+
+use color_eyre::eyre::{Result, WrapErr};
+use serde::Deserialize;
+
+use crate::params::Overrides;
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// Settings read from `aoc.toml`; every key is optional.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Value of the `session` cookie of a logged in adventofcode.com account.
+    pub session: Option<String>,
+    pub base_url: String,
+    /// Minimum number of seconds between two requests to the server.
+    pub min_request_interval: u64,
+    /// Puzzle parameters replacing the defaults, one `[params.dayNN]` table per day.
+    pub params: Overrides,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            session: None,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            min_request_interval: 5,
+            params: Overrides::default(),
+        }
+    }
+}
+
+impl Config {
+    pub fn parse(config: &str) -> Result<Self> {
+        Ok(toml::from_str(config)?)
+    }
+
+    /// Reads the config at `path`, a missing file meaning the defaults.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let config = fs::read_to_string(path)
+            .wrap_err_with(|| format!("could not read config {}", path.display()))?;
+        Self::parse(&config).wrap_err_with(|| format!("invalid config {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn params_are_read_per_day() {
+        let config = Config::parse("[params.day11]\npart2_rounds = 30").unwrap();
+        let rounds = config.params.day(11).get("part2_rounds").cloned();
+        assert_eq!(rounds, Some(toml::Value::Integer(30)));
+        assert!(config.params.day(1).is_empty());
+        assert!(Config::parse("[params.monday]\nrounds = 1").is_err());
+    }
+
+    #[test]
+    fn missing_keys_use_the_defaults() {
+        let config = Config::parse("session = \"abc\"").unwrap();
+        assert_eq!(
+            config,
+            Config {
+                session: Some("abc".to_string()),
+                ..Config::default()
+            }
+        );
+        assert_eq!(
+            Config::load(Path::new("does/not/exist.toml")).unwrap(),
+            Config::default()
+        );
+    }
+}
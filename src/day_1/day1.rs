@@ -1,39 +1,78 @@
 use itertools::Itertools;
 use std::cmp::Reverse;
 
-const INPUT: &str = include_str!("./input.txt");
+use crate::generate::{Generate, Rng};
+use crate::params::NoParams;
+use crate::parse::{parse_lines, parse_num, ParseError};
+use crate::solution::{Answer, Solution};
+
+pub(crate) const INPUT: &str = include_str!("./input.txt");
 #[allow(dead_code)]
 const TEST_INPUT: &str = include_str!("./test_input.txt");
 
-pub fn run() {
-    println!("=== Day 1 ===");
-    part1(INPUT);
-    part2(INPUT);
-    println!("=============");
-    println!();
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+
+    type Input = Vec<u64>;
+    type Params = NoParams;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        read_input(input)
+    }
+
+    fn part1(input: &Self::Input, _params: &Self::Params) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input, _params: &Self::Params) -> Answer {
+        part2(input).into()
+    }
 }
 
-fn part1(input: &str) -> u64 {
-    let max = input
-        .lines()
-        .map(|v| v.parse::<u64>().ok())
-        .batching(|it| it.map_while(|x| x).sum1::<u64>())
-        .max();
-    println!("Day 1 part 1, solution: {}", max.unwrap_or(0));
-    max.unwrap_or(0)
+impl Generate for Day1 {
+    const DEFAULT_SIZE: usize = 250;
+
+    /// `size` elves carrying one to fifteen snacks each.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let elves: Vec<String> = (0..size)
+            .map(|_| {
+                let snacks: Vec<String> = (0..rng.usize(1..=15))
+                    .map(|_| rng.range(1000..=70000).to_string())
+                    .collect();
+                snacks.join("\n")
+            })
+            .collect();
+        elves.join("\n\n")
+    }
 }
 
-fn part2(input: &str) -> u64 {
-    let sum = input
-        .lines()
-        .map(|v| v.parse::<u64>().ok())
+fn read_input(input: &str) -> Result<Vec<u64>, ParseError> {
+    let calories = parse_lines(Day1::DAY, input.lines(), |line| {
+        if line.is_empty() {
+            return Ok(None);
+        }
+        parse_num::<u64>(line, "calories").map(Some)
+    })?;
+    Ok(calories
+        .into_iter()
         .batching(|it| it.map_while(|x| x).sum1::<u64>())
+        .collect())
+}
+
+fn part1(calories: &[u64]) -> u64 {
+    calories.iter().copied().max().unwrap_or(0)
+}
+
+fn part2(calories: &[u64]) -> u64 {
+    calories
+        .iter()
+        .copied()
         .map(Reverse)
         .k_smallest(3)
         .map(|v| v.0)
-        .sum();
-    println!("Day 1 part 2, solution: {}", sum);
-    sum
+        .sum()
 }
 
 #[cfg(test)]
@@ -43,21 +82,27 @@ mod tests {
 
     #[test]
     fn part1_works_with_test_input() {
-        assert_eq!(part1(TEST_INPUT), 24000);
+        assert_eq!(part1(&read_input(TEST_INPUT).unwrap()), 24000);
     }
 
     #[test]
     fn part1_works_with_puzzle_input() {
-        assert_eq!(part1(INPUT), 68787);
+        assert_eq!(part1(&read_input(INPUT).unwrap()), 68787);
     }
 
     #[test]
     fn part2_works_with_test_input() {
-        assert_eq!(part2(TEST_INPUT), 45000);
+        assert_eq!(part2(&read_input(TEST_INPUT).unwrap()), 45000);
     }
 
     #[test]
     fn part2_works_with_puzzle_input() {
-        assert_eq!(part2(INPUT), 198041);
+        assert_eq!(part2(&read_input(INPUT).unwrap()), 198041);
+    }
+
+    #[test]
+    fn read_input_reports_invalid_calories() {
+        let err = read_input("1000\n\n2x00").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (3, 1, "2x00"));
     }
 }
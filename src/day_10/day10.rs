@@ -1,19 +1,26 @@
-#[allow(dead_code)]
+use serde::Deserialize;
+
+use crate::generate::{Generate, Rng};
+use crate::params::Validate;
+use crate::parse::{parse_lines, parse_num, ParseError};
+use crate::solution::{Answer, Solution};
+use crate::trace::{self, Event};
 
-const INPUT: &str = include_str!("./input.txt");
+pub(crate) const INPUT: &str = include_str!("./input.txt");
 #[allow(dead_code)]
 const TEST_INPUT: &str = include_str!("./test_input.txt");
 
 #[derive(Debug)]
-enum Instruction {
+pub enum Instruction {
     Addx,
     Noop,
 }
 
 #[derive(Debug)]
-struct Program {
-    inst_type: Instruction,
-    value: Option<i32>,
+pub struct Program {
+    pub inst_type: Instruction,
+    /// Operand of `addx`.
+    pub value: Option<i32>,
 }
 
 #[allow(dead_code)]
@@ -23,8 +30,8 @@ struct Crt {
     size: (u8, u8),
 }
 
+#[allow(dead_code)]
 impl Crt {
-    #[allow(dead_code)]
     fn new(new_value: &'static str, size: (u8, u8)) -> Self {
         let mut crt = Vec::<Vec<&'static str>>::new();
         let row = vec![new_value; (size.0 * size.1) as usize];
@@ -53,48 +60,86 @@ impl Program {
     }
 }
 
-fn read_input(input: &str) -> Vec<Program> {
-    let mut program = Vec::<Program>::new();
-    for line in input.split("\n") {
-        let line: Vec<&str> = line.trim().split(" ").collect();
-        match line[0] {
-            "addx" => program.push(Program::new(
-                Instruction::Addx,
-                Some(
-                    line[1]
-                        .parse::<i32>()
-                        .unwrap_or_else(|_| panic!("Could not parse value ({}) to i32", line[1])),
-                ),
-            )),
-            "noop" => program.push(Program::new(Instruction::Noop, None)),
-            _ => {
-                panic!("Unrecognized instruction ({}) encountered!", line[0])
+fn read_input(input: &str) -> Result<Vec<Program>, ParseError> {
+    parse_lines(Day10::DAY, input.lines(), |line| {
+        let trimmed = line.trim();
+        let (opcode, value) = trimmed.split_once(' ').unwrap_or((trimmed, ""));
+        match opcode {
+            "addx" => {
+                let value =
+                    parse_num::<i32>(value, "addx value").map_err(|e| e.within(line, value))?;
+                Ok(Program::new(Instruction::Addx, Some(value)))
             }
+            "noop" => Ok(Program::new(Instruction::Noop, None)),
+            _ => Err(ParseError::new("unrecognized instruction", opcode).within(line, opcode)),
         }
-    }
-    program
+    })
 }
 
-pub fn run() {
-    println!("=== Day 10 ===");
-    println!("Day 10 part 1, solution: {}", part1(INPUT));
-    //println!("Day 10 part 2, solution: {}", solve(INPUT, 14));
-    println!("=============");
-    println!();
+/// Cycles during which the signal strength is sampled.
+const SAMPLE_CYCLES: [i32; 6] = [20, 60, 100, 140, 180, 220];
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    pub sample_cycles: Vec<i32>,
 }
 
-fn calculate_signal_strength(cycle: i32, register: i32) -> i32 {
-    match cycle {
-        20 | 60 | 100 | 140 | 180 | 220 => {
-            dbg!(register * cycle);
-            register * cycle
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            sample_cycles: SAMPLE_CYCLES.to_vec(),
         }
-        _ => 0,
     }
 }
 
-fn part1(input: &str) -> i32 {
-    let program = read_input(input);
+impl Validate for Params {}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+
+    type Input = Vec<Program>;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        read_input(input)
+    }
+
+    fn part1(input: &Self::Input, params: &Params) -> Answer {
+        part1(input, &params.sample_cycles).into()
+    }
+}
+
+impl Generate for Day10 {
+    const DEFAULT_SIZE: usize = 140;
+
+    /// `size` instructions.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let program: Vec<String> = (0..size)
+            .map(|_| {
+                if rng.chance(40) {
+                    "noop".to_string()
+                } else {
+                    format!("addx {}", rng.range(-20..=20))
+                }
+            })
+            .collect();
+        program.join("\n")
+    }
+}
+
+fn calculate_signal_strength(cycle: i32, register: i32, samples: &[i32]) -> i32 {
+    if samples.contains(&cycle) {
+        trace::emit(|| Event::SignalSampled { cycle, register });
+        register * cycle
+    } else {
+        0
+    }
+}
+
+fn part1(program: &[Program], samples: &[i32]) -> i32 {
     let mut cycles = 0;
     let mut register = 1;
     let mut signal_strength = 0;
@@ -103,20 +148,21 @@ fn part1(input: &str) -> i32 {
             Instruction::Addx => {
                 for cycle in [1, 1] {
                     cycles += cycle;
-                    signal_strength += calculate_signal_strength(cycles, register);
+                    signal_strength += calculate_signal_strength(cycles, register, samples);
                 }
                 register += instruction.value.unwrap();
             }
             Instruction::Noop => {
                 cycles += 1;
-                signal_strength += calculate_signal_strength(cycles, register);
+                signal_strength += calculate_signal_strength(cycles, register, samples);
             }
         }
     }
     signal_strength
 }
 
-fn part2(_input: &str) {
+#[allow(dead_code)]
+fn part2(_program: &[Program]) {
     let _crt = Crt::new("#", (6, 40));
 }
 
@@ -127,16 +173,42 @@ mod tests {
 
     #[test]
     fn part1_works_with_test_input() {
-        assert_eq!(part1(TEST_INPUT), 13140);
+        assert_eq!(
+            part1(&read_input(TEST_INPUT).unwrap(), &SAMPLE_CYCLES),
+            13140
+        );
     }
 
     #[test]
     fn part1_works_with_puzzle_input() {
-        assert_eq!(part1(INPUT), 14540);
+        assert_eq!(part1(&read_input(INPUT).unwrap(), &SAMPLE_CYCLES), 14540);
+    }
+
+    #[test]
+    fn every_sample_is_traced() {
+        let program = read_input(TEST_INPUT).unwrap();
+        let (strength, events) = trace::record(|| part1(&program, &SAMPLE_CYCLES));
+        let sampled: Vec<i32> = events
+            .iter()
+            .map(|event| match event {
+                Event::SignalSampled { cycle, register } => cycle * register,
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        assert_eq!(sampled.len(), SAMPLE_CYCLES.len());
+        assert_eq!(sampled.iter().sum::<i32>(), strength);
     }
 
     #[test]
     fn part2_works_with_test_input() {
-        part2(TEST_INPUT)
+        part2(&read_input(TEST_INPUT).unwrap())
+    }
+
+    #[test]
+    fn read_input_reports_invalid_instructions() {
+        let err = read_input("noop\naddx 3\nmulx 2").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (3, 1, "mulx"));
+        let err = read_input("noop\naddx x").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 6, "x"));
     }
 }
@@ -1,33 +1,42 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::str::FromStr;
 
 use itertools::Itertools;
 use regex::Regex;
+use serde::Deserialize;
 
-const INPUT: &str = include_str!("./input.txt");
+use crate::generate::{Generate, Rng};
+use crate::params::Validate;
+use crate::parse::{parse_lines, parse_num, ParseError};
+use crate::solution::{Answer, Solution};
+use crate::trace::{self, Event};
 
+pub(crate) const INPUT: &str = include_str!("./input.txt");
+#[allow(dead_code)]
 const TEST_INPUT: &str = include_str!("./test_input.txt");
 
-const PART1_ROUNDS: u8 = 20;
+const PART1_ROUNDS: u32 = 20;
 const PART2_ROUNDS: u32 = 10000;
 
-#[derive(Debug, Eq, PartialEq)]
-enum OperationType {
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum OperationType {
     Plus,
     Multiple,
 }
 
-#[derive(Debug, Eq, PartialEq)]
-struct Monkey {
-    number: u8,
-    items: Vec<u64>,
-    operation_value: u64,
-    operation_type: OperationType,
-    division: u64,
-    throw: HashMap<bool, u8>,
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Monkey {
+    pub number: u8,
+    pub items: Vec<u64>,
+    /// Right hand side of the operation, 0 standing for `old`.
+    pub operation_value: u64,
+    pub operation_type: OperationType,
+    pub division: u64,
+    /// Monkey the item goes to, by the outcome of the divisibility test.
+    pub throw: HashMap<bool, u8>,
     inspects: u32,
-    common_multiple: u32,
 }
 
 impl Monkey {
@@ -40,7 +49,6 @@ impl Monkey {
             division: 0,
             throw: HashMap::<_, _>::new(),
             inspects: 0,
-            common_multiple: 0,
         }
     }
 
@@ -75,7 +83,7 @@ impl Monkey {
     }
 
     fn test(&self, value: u64) -> u8 {
-        if value % self.division == 0 {
+        if value.is_multiple_of(self.division) {
             self.throw[&true]
         } else {
             self.throw[&false]
@@ -91,90 +99,265 @@ impl Monkey {
     }
 }
 
-fn read_monkeys(input: &str) -> HashMap<u8, RefCell<Monkey>> {
-    let mut monkeys = HashMap::<u8, RefCell<Monkey>>::new();
-    let re_monkey_num = Regex::new(r"^Monkey (\d):$").unwrap();
+fn number<T: FromStr>(line: &str, text: &str) -> Result<T, ParseError> {
+    parse_num::<T>(text, "number").map_err(|e| e.within(line, text))
+}
+
+/// Notes every monkey needs, besides its `Monkey <n>:` header.
+const NOTES: [&str; 5] = ["Starting items", "Operation", "Test", "If true", "If false"];
+
+/// The notes read for a monkey, to check that none is missing once it is complete.
+struct Notes {
+    number: u8,
+    seen: Vec<&'static str>,
+}
+
+/// What a line turned out to hold, for the checks that can only be made once every monkey is
+/// read.
+enum Line<'a> {
+    Header,
+    /// A throw to another monkey, which has to exist.
+    Throw {
+        line: &'a str,
+        target: &'a str,
+        to: u8,
+    },
+    Other,
+}
+
+fn read_monkeys(input: &str) -> Result<Monkeys, ParseError> {
+    let mut monkeys = Monkeys::new();
+    let re_monkey_num = Regex::new(r"^Monkey (\d+):$").unwrap();
     let re_monkey_items = Regex::new(r"\s*Starting items: (.*)$").unwrap();
     let re_monkey_operation = Regex::new(r"\s*Operation: new = old ([+*]) (\d{1,2}|old)$").unwrap();
     let re_monkey_division = Regex::new(r"\s*Test: divisible by (\d{1,2})$").unwrap();
     let re_monkey_test = Regex::new(r"\s*If (true|false): throw to monkey (\d{1,2})$").unwrap();
-    for c in input.split("\n\n") {
-        let mut monkey = Monkey::new();
-        for data in c.lines() {
-            let data = data.trim();
-            if re_monkey_num.is_match(data) {
-                for cap in re_monkey_num.captures_iter(data) {
-                    monkey.number = cap[1].parse::<u8>().unwrap();
-                }
-                continue;
+    let mut current: Option<Monkey> = None;
+    let mut notes: Vec<Notes> = Vec::new();
+    let lines = parse_lines(Day11::DAY, input.lines(), |line| {
+        let data = line.trim();
+        if data.is_empty() {
+            return Ok(Line::Other);
+        }
+        if let Some(cap) = re_monkey_num.captures(data) {
+            if let Some(monkey) = current.take() {
+                monkeys.insert(monkey.number, RefCell::new(monkey));
             }
-            if re_monkey_items.is_match(data) {
-                for cap in re_monkey_items.captures_iter(data) {
-                    for num in cap[1].split(',') {
-                        monkey.items.push(num.trim().parse::<u64>().unwrap());
-                    }
-                }
-                continue;
+            let mut monkey = Monkey::new();
+            monkey.number = number(line, &cap[1])?;
+            if monkeys.contains_key(&monkey.number) {
+                return Err(ParseError::new("a second monkey with this number", &cap[1])
+                    .within(line, cap.get(1).unwrap().as_str()));
             }
-            if re_monkey_operation.is_match(data) {
-                for cap in re_monkey_operation.captures_iter(data) {
-                    match &cap[1] {
-                        "+" => monkey.operation_type = OperationType::Plus,
-                        "*" => monkey.operation_type = OperationType::Multiple,
-                        _ => {}
-                    }
-                    if &cap[2] == "old" {
-                        monkey.operation_value = 0
-                    } else {
-                        monkey.operation_value = cap[2].parse::<u64>().unwrap();
-                    }
-                }
-                continue;
+            notes.push(Notes {
+                number: monkey.number,
+                seen: Vec::new(),
+            });
+            current = Some(monkey);
+            return Ok(Line::Header);
+        }
+        let monkey = current
+            .as_mut()
+            .ok_or_else(|| ParseError::new("expected Monkey <n>:", data).within(line, data))?;
+        let mut read = Line::Other;
+        let note = if let Some(cap) = re_monkey_items.captures(data) {
+            for num in cap.get(1).unwrap().as_str().split(',') {
+                monkey.items.push(number(line, num.trim())?);
             }
-            if re_monkey_division.is_match(data) {
-                for cap in re_monkey_division.captures_iter(data) {
-                    monkey.division = cap[1].parse::<u64>().unwrap();
-                }
-                continue;
+            NOTES[0]
+        } else if let Some(cap) = re_monkey_operation.captures(data) {
+            match &cap[1] {
+                "+" => monkey.operation_type = OperationType::Plus,
+                "*" => monkey.operation_type = OperationType::Multiple,
+                _ => {}
             }
-            if re_monkey_test.is_match(data) {
-                for cap in re_monkey_test.captures_iter(data) {
-                    if &cap[1] == "true" {
-                        monkey.throw.insert(true, cap[2].parse::<u8>().unwrap());
-                    }
-                    if &cap[1] == "false" {
-                        monkey.throw.insert(false, cap[2].parse::<u8>().unwrap());
-                    }
-                }
-                continue;
+            if &cap[2] == "old" {
+                monkey.operation_value = 0
+            } else {
+                monkey.operation_value = number(line, &cap[2])?;
             }
+            NOTES[1]
+        } else if let Some(cap) = re_monkey_division.captures(data) {
+            let divisor = cap.get(1).unwrap().as_str();
+            monkey.division = number(line, divisor)?;
+            if monkey.division == 0 {
+                return Err(
+                    ParseError::new("cannot test divisibility by 0", divisor).within(line, divisor)
+                );
+            }
+            NOTES[2]
+        } else if let Some(cap) = re_monkey_test.captures(data) {
+            let target = cap.get(2).unwrap().as_str();
+            let to = number(line, target)?;
+            if to == monkey.number {
+                return Err(ParseError::new("monkey throws to itself", target).within(line, target));
+            }
+            monkey.throw.insert(&cap[1] == "true", to);
+            read = Line::Throw { line, target, to };
+            if &cap[1] == "true" {
+                NOTES[3]
+            } else {
+                NOTES[4]
+            }
+        } else {
+            return Err(ParseError::new("unrecognized note", data).within(line, data));
+        };
+        let seen = &mut notes.last_mut().expect("a monkey is being read").seen;
+        if seen.contains(&note) {
+            return Err(ParseError::new(format!("a second {note} note"), data).within(line, data));
         }
+        seen.push(note);
+        Ok(read)
+    })?;
+    if let Some(monkey) = current {
         monkeys.insert(monkey.number, RefCell::new(monkey));
     }
-    monkeys
+    if monkeys.is_empty() {
+        return Err(ParseError::new("expected at least one monkey", "").at_line(Day11::DAY, 1));
+    }
+    let headers = lines.iter().positions(|line| matches!(line, Line::Header));
+    for (notes, header) in notes.iter().zip(headers) {
+        if let Some(note) = NOTES.iter().find(|note| !notes.seen.contains(note)) {
+            return Err(ParseError::new(
+                format!("monkey {} has no {note} note", notes.number),
+                format!("Monkey {}:", notes.number),
+            )
+            .at_line(Day11::DAY, header + 1));
+        }
+    }
+    for (index, read) in lines.iter().enumerate() {
+        if let Line::Throw { line, target, to } = *read {
+            if !monkeys.contains_key(&to) {
+                return Err(ParseError::new("no monkey with this number", target)
+                    .within(line, target)
+                    .at_line(Day11::DAY, index + 1));
+            }
+        }
+    }
+    Ok(monkeys)
+}
+
+pub type Monkeys = HashMap<u8, RefCell<Monkey>>;
+
+/// Number of rounds the monkeys play in each part.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    pub part1_rounds: u32,
+    pub part2_rounds: u32,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            part1_rounds: PART1_ROUNDS,
+            part2_rounds: PART2_ROUNDS,
+        }
+    }
+}
+
+/// Most rounds a part may play, enough to take a few seconds.
+const MAX_ROUNDS: u32 = 1_000_000;
+
+impl Validate for Params {
+    fn validate(&self) -> color_eyre::Result<()> {
+        for (name, rounds) in [
+            ("part1_rounds", self.part1_rounds),
+            ("part2_rounds", self.part2_rounds),
+        ] {
+            if rounds > MAX_ROUNDS {
+                color_eyre::eyre::bail!("{name} must be at most {MAX_ROUNDS}, got {rounds}");
+            }
+        }
+        Ok(())
+    }
 }
 
-pub fn run() {
-    println!("=== Day 11 ===");
-    part1(INPUT);
-    part2(INPUT);
-    println!("=============");
-    println!();
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+
+    type Input = Monkeys;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        read_monkeys(input)
+    }
+
+    fn part1(input: &Self::Input, params: &Params) -> Answer {
+        part1(input, params.part1_rounds).into()
+    }
+
+    fn part2(input: &Self::Input, params: &Params) -> Answer {
+        part2(input, params.part2_rounds).into()
+    }
+}
+
+impl Generate for Day11 {
+    const DEFAULT_SIZE: usize = 8;
+
+    /// `size` monkeys, two to ten, each testing for a different prime.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let count = size.clamp(2, 10);
+        let mut primes = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29];
+        rng.shuffle(&mut primes);
+        let monkeys: Vec<String> = (0..count)
+            .map(|number| {
+                let items: Vec<String> = (0..rng.usize(1..=8))
+                    .map(|_| rng.range(50..=99).to_string())
+                    .collect();
+                let operation = match rng.below(3) {
+                    0 => format!("+ {}", rng.range(1..=8)),
+                    1 => format!("* {}", rng.range(2..=19)),
+                    _ => "+ old".to_string(),
+                };
+                let if_true = (number + rng.usize(1..=count - 1)) % count;
+                let mut if_false = (number + rng.usize(1..=count - 1)) % count;
+                if if_false == if_true && count > 2 {
+                    if_false = (1..count)
+                        .map(|step| (if_true + step) % count)
+                        .find(|&other| other != number)
+                        .unwrap();
+                }
+                format!(
+                    "Monkey {number}:\n  \
+                     Starting items: {}\n  \
+                     Operation: new = old {operation}\n  \
+                     Test: divisible by {}\n    \
+                     If true: throw to monkey {if_true}\n    \
+                     If false: throw to monkey {if_false}",
+                    items.join(", "),
+                    primes[number]
+                )
+            })
+            .collect();
+        monkeys.join("\n\n")
+    }
 }
 
-fn calculate_monkey_business(monkeys: &HashMap<u8, RefCell<Monkey>>) -> u64 {
+fn calculate_monkey_business(monkeys: &Monkeys) -> u64 {
     monkeys
-        .iter()
-        .map(|(_, monkey)| monkey.borrow().inspects as u64)
+        .values()
+        .map(|monkey| monkey.borrow().inspects as u64)
         .sorted()
         .rev()
         .take(2)
         .product()
 }
 
-fn part1(input: &str) -> u64 {
-    let monkeys = read_monkeys(input);
-    for _ in 0..PART1_ROUNDS {
+fn round_finished(round: u32, monkeys: &Monkeys) -> Event {
+    let inspections = monkeys
+        .iter()
+        .sorted_by_key(|x| x.0)
+        .map(|(_, monkey)| monkey.borrow().inspects as u64)
+        .collect();
+    Event::RoundFinished { round, inspections }
+}
+
+fn part1(monkeys: &Monkeys, rounds: u32) -> u64 {
+    let monkeys = monkeys.clone();
+    for round in 1..=rounds {
         for (_, monkey) in monkeys.iter().sorted_by_key(|x| x.0) {
             if monkey.borrow().items.is_empty() {
                 continue;
@@ -185,24 +368,25 @@ fn part1(input: &str) -> u64 {
                 let throw_to = monkey.borrow().test(worry_level);
                 monkeys
                     .get(&throw_to)
-                    .unwrap()
+                    .expect("the parser checks that every target exists")
                     .borrow_mut()
                     .items
                     .push(worry_level);
             }
             monkey.borrow_mut().clear();
         }
+        trace::emit(|| round_finished(round, &monkeys));
     }
-    let result = calculate_monkey_business(&monkeys);
-    println!("Day 11 part 1, solution: {}", result);
-    result
+    calculate_monkey_business(&monkeys)
 }
 
-fn part2(input: &str) -> u64 {
-    let monkeys = read_monkeys(input);
+fn part2(monkeys: &Monkeys, rounds: u32) -> u64 {
+    let monkeys = monkeys.clone();
     let common_multiple: u64 = monkeys.iter().map(|m| m.1.borrow().division).product();
-    dbg!(common_multiple);
-    for _ in 0..PART2_ROUNDS {
+    trace::emit(|| Event::WorryModulus {
+        modulus: common_multiple,
+    });
+    for round in 1..=rounds {
         for (_, monkey) in monkeys.iter().sorted_by_key(|x| x.0) {
             if monkey.borrow().items.is_empty() {
                 continue;
@@ -213,17 +397,16 @@ fn part2(input: &str) -> u64 {
                 let throw_to = monkey.borrow().test(worry_level);
                 monkeys
                     .get(&throw_to)
-                    .unwrap()
+                    .expect("the parser checks that every target exists")
                     .borrow_mut()
                     .items
                     .push(worry_level);
             }
             monkey.borrow_mut().clear();
         }
+        trace::emit(|| round_finished(round, &monkeys));
     }
-    let result = calculate_monkey_business(&monkeys);
-    println!("Day 11 part 2, solution: {}", result);
-    result
+    calculate_monkey_business(&monkeys)
 }
 
 #[cfg(test)]
@@ -231,24 +414,111 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::params;
 
     #[test]
     fn part1_works_with_test_input() {
-        assert_eq!(part1(TEST_INPUT), 10605);
+        assert_eq!(
+            part1(&read_monkeys(TEST_INPUT).unwrap(), PART1_ROUNDS),
+            10605
+        );
     }
 
     #[test]
     fn part1_works_with_puzzle_input() {
-        assert_eq!(part1(INPUT), 55944);
+        assert_eq!(part1(&read_monkeys(INPUT).unwrap(), PART1_ROUNDS), 55944);
     }
 
     #[test]
     fn part2_works_with_test_input() {
-        assert_eq!(part2(TEST_INPUT), 2713310158);
+        assert_eq!(
+            part2(&read_monkeys(TEST_INPUT).unwrap(), PART2_ROUNDS),
+            2713310158
+        );
     }
 
     #[test]
     fn part2_works_with_puzzle_input() {
-        assert_eq!(part2(INPUT), 15117269860);
+        assert_eq!(
+            part2(&read_monkeys(INPUT).unwrap(), PART2_ROUNDS),
+            15117269860
+        );
+    }
+
+    #[test]
+    fn every_round_is_traced() {
+        let monkeys = read_monkeys(TEST_INPUT).unwrap();
+        let (business, events) = trace::record(|| part1(&monkeys, PART1_ROUNDS));
+        assert_eq!(events.len(), PART1_ROUNDS as usize);
+        let Some(Event::RoundFinished { round, inspections }) = events.last() else {
+            panic!("unexpected {events:?}");
+        };
+        assert_eq!(
+            (*round, inspections.as_slice()),
+            (20, &[101, 95, 7, 105][..])
+        );
+        assert_eq!(business, 101 * 105);
+    }
+
+    #[test]
+    fn read_monkeys_reports_invalid_notes() {
+        let input = "Monkey 0:\n  Starting items: 79, 9x\n";
+        let err = read_monkeys(input).unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 23, "9x"));
+        let err = read_monkeys("Monkey 0:\n  Operation: new = old / 2").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+    }
+
+    #[test]
+    fn read_monkeys_reports_missing_notes_and_monkeys() {
+        let monkey = |number: usize, target: usize| {
+            format!(
+                "Monkey {number}:\n  Starting items: 79\n  Operation: new = old * 19\n  \
+                 Test: divisible by 23\n    If true: throw to monkey {target}\n    \
+                 If false: throw to monkey {target}\n"
+            )
+        };
+        let input = [monkey(0, 1), monkey(1, 0)].join("\n");
+        assert_eq!(read_monkeys(&input).unwrap().len(), 2);
+
+        let input = [monkey(0, 1), monkey(1, 2)].join("\n");
+        let err = read_monkeys(&input).unwrap_err();
+        assert_eq!(
+            (err.line, err.column, err.reason.as_str()),
+            (12, 30, "no monkey with this number")
+        );
+
+        let input = input.replace("  Operation: new = old * 19\n", "");
+        let err = read_monkeys(&input).unwrap_err();
+        assert_eq!(
+            (err.line, err.reason.as_str()),
+            (1, "monkey 0 has no Operation note")
+        );
+
+        let input = [monkey(12, 13), monkey(13, 12)].join("\n");
+        assert!(read_monkeys(&input).unwrap().contains_key(&12));
+        let err = read_monkeys(&input.replace("by 23", "by 0")).unwrap_err();
+        assert_eq!(
+            (err.line, err.column, err.reason.as_str()),
+            (4, 22, "cannot test divisibility by 0")
+        );
+        let err = read_monkeys("").unwrap_err();
+        assert_eq!(err.reason, "expected at least one monkey");
+
+        let err = read_monkeys(&monkey(0, 0)).unwrap_err();
+        assert_eq!(
+            (err.line, err.column, err.reason.as_str()),
+            (5, 30, "monkey throws to itself")
+        );
+    }
+
+    #[test]
+    fn rounds_are_bounded() {
+        let overrides = toml::toml! { part2_rounds = 4_000_000_000_i64 };
+        let err = params::resolve::<Params>(&overrides).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid parameters: part2_rounds must be at most 1000000, got 4000000000"
+        );
     }
 }
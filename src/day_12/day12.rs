@@ -0,0 +1,236 @@
+use crate::generate::{Generate, Rng};
+use crate::geometry::{Direction, Point};
+use crate::grid::Grid;
+use crate::params::NoParams;
+use crate::parse::ParseError;
+use crate::search;
+use crate::solution::{Answer, Solution};
+
+pub(crate) const INPUT: &str = include_str!("./input.txt");
+#[allow(dead_code)]
+const TEST_INPUT: &str = include_str!("./test_input.txt");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heightmap {
+    /// Elevation of every square, 0 for `a` up to 25 for `z`.
+    pub heights: Grid<u8>,
+    /// The current position, `S`, at elevation `a`.
+    pub start: Point,
+    /// The location with the best signal, `E`, at elevation `z`.
+    pub end: Point,
+}
+
+impl Heightmap {
+    /// The squares one step from `from` that are at most one higher.
+    fn climbs(&self, from: Point) -> impl Iterator<Item = Point> + '_ {
+        let limit = self.heights[from] + 1;
+        self.heights
+            .neighbours4(from)
+            .filter(move |(_, &height)| height <= limit)
+            .map(|(square, _)| square)
+    }
+
+    /// A shortest climb to the end from the nearest of `starts`, both ends included.
+    pub fn shortest_path(&self, starts: impl IntoIterator<Item = Point>) -> Option<Vec<Point>> {
+        search::shortest_path(
+            starts,
+            |&square| self.climbs(square),
+            |&square| square == self.end,
+        )
+    }
+
+    /// Every square at elevation `a`.
+    pub fn lowest(&self) -> impl Iterator<Item = Point> + '_ {
+        self.heights
+            .iter()
+            .filter(|(_, &height)| height == 0)
+            .map(|(square, _)| square)
+    }
+
+    /// The map with `path` drawn on it as in the puzzle: an arrow on each square pointing to the
+    /// next one, `E` on the last and `.` everywhere else.
+    pub fn render(&self, path: &[Point]) -> String {
+        let (width, height) = (self.heights.width(), self.heights.height());
+        let mut map = Grid::from_fn(self.heights.min(), width, height, |_| '.');
+        for step in path.windows(2) {
+            let direction =
+                Direction::from_offset(step[1] - step[0]).expect("path steps are adjacent");
+            map[step[0]] = direction.arrow();
+        }
+        if let Some(&last) = path.last() {
+            map[last] = 'E';
+        }
+        map.to_string()
+    }
+}
+
+fn read_heightmap(input: &str) -> Result<Heightmap, ParseError> {
+    let squares = Grid::parse(Day12::DAY, input, "square", |c| {
+        matches!(c, 'a'..='z' | 'S' | 'E').then_some(c)
+    })?;
+    let find = |marker: char, name: &str| -> Result<Point, ParseError> {
+        let mut found = squares
+            .iter()
+            .filter(|(_, &c)| c == marker)
+            .map(|(square, _)| square);
+        let first = found.next().ok_or_else(|| {
+            ParseError::new(format!("no {name} square {marker}"), "").at_line(Day12::DAY, 1)
+        })?;
+        if let Some(second) = found.next() {
+            return Err(
+                ParseError::new(format!("a second {name} square"), marker.to_string())
+                    .at_column(second.x as usize + 1)
+                    .at_line(Day12::DAY, second.y as usize + 1),
+            );
+        }
+        Ok(first)
+    };
+    let (start, end) = (find('S', "start")?, find('E', "end")?);
+    let heights =
+        Grid::from_fn(
+            Point::ORIGIN,
+            squares.width(),
+            squares.height(),
+            |square| match squares[square] {
+                'S' => 0,
+                'E' => 25,
+                c => c as u8 - b'a',
+            },
+        );
+    Ok(Heightmap {
+        heights,
+        start,
+        end,
+    })
+}
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+
+    type Input = Heightmap;
+    type Params = NoParams;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        read_heightmap(input)
+    }
+
+    fn part1(input: &Self::Input, _params: &Self::Params) -> Answer {
+        part1(input).map_or_else(|| unreachable("S"), Answer::from)
+    }
+
+    fn part2(input: &Self::Input, _params: &Self::Params) -> Answer {
+        part2(input).map_or_else(|| unreachable("any square at elevation a"), Answer::from)
+    }
+}
+
+impl Generate for Day12 {
+    const DEFAULT_SIZE: usize = 160;
+
+    /// A map `size` squares wide, at least 26, rising from `a` on the left to `z` on the right
+    /// with random dips. The row of `S` and the last column have none, so `E` can be reached.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let width = size.max(26);
+        let height = (width / 4).max(5);
+        let (start_row, end_row) = (rng.usize(0..=height - 1), rng.usize(0..=height - 1));
+        let rows: Vec<String> = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| {
+                        let rise = (x * 25 / (width - 1)) as u8;
+                        let dip = if y == start_row || x == width - 1 || rng.chance(40) {
+                            0
+                        } else {
+                            rng.below(4) as u8
+                        };
+                        match (x, y) {
+                            (0, y) if y == start_row => 'S',
+                            (x, y) if x == width - 1 && y == end_row => 'E',
+                            _ => (b'a' + rise.saturating_sub(dip)) as char,
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        rows.join("\n")
+    }
+}
+
+fn unreachable(from: &str) -> Answer {
+    Answer::NoAnswer(format!("the end square E cannot be reached from {from}"))
+}
+
+/// Fewest steps from the start to the end, if there is a climb at all.
+fn part1(heightmap: &Heightmap) -> Option<usize> {
+    let path = heightmap.shortest_path([heightmap.start])?;
+    Some(path.len() - 1)
+}
+
+/// Fewest steps to the end from any square at elevation `a`, if there is a climb at all.
+fn part2(heightmap: &Heightmap) -> Option<usize> {
+    let path = heightmap.shortest_path(heightmap.lowest())?;
+    Some(path.len() - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn part1_works_with_test_input() {
+        assert_eq!(part1(&read_heightmap(TEST_INPUT).unwrap()), Some(31));
+    }
+
+    #[test]
+    fn part2_works_with_test_input() {
+        assert_eq!(part2(&read_heightmap(TEST_INPUT).unwrap()), Some(29));
+    }
+
+    #[test]
+    fn paths_climb_at_most_one_step_and_render_as_arrows() {
+        let heightmap = read_heightmap(TEST_INPUT).unwrap();
+        let path = heightmap.shortest_path([heightmap.start]).unwrap();
+        assert_eq!(
+            (path[0], *path.last().unwrap()),
+            (heightmap.start, heightmap.end)
+        );
+        for step in path.windows(2) {
+            assert_eq!(step[0].manhattan(step[1]), 1);
+            assert!(heightmap.heights[step[1]] <= heightmap.heights[step[0]] + 1);
+        }
+
+        let map = heightmap.render(&path);
+        assert_eq!(map.lines().count(), 5);
+        assert_eq!(map.chars().filter(|c| "^>v<".contains(*c)).count(), 31);
+        assert_eq!(map.lines().nth(2).unwrap().find('E'), Some(5));
+        assert!(map.starts_with('v') || map.starts_with('>'));
+    }
+
+    #[test]
+    fn read_heightmap_reports_invalid_maps() {
+        let err = read_heightmap("Sab\nabE\naB").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (3, 2, "B"));
+        let err = read_heightmap("Sab\nabE\nSbc").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (3, 1, "S"));
+        let err = read_heightmap("aab\nabE").unwrap_err();
+        assert_eq!(err.reason, "no start square S");
+    }
+
+    #[test]
+    fn unreachable_end_has_no_answer() {
+        let heightmap = read_heightmap("Sz\nzE").unwrap();
+        assert_eq!(
+            Day12::part1(&heightmap, &NoParams),
+            Answer::NoAnswer("the end square E cannot be reached from S".to_string())
+        );
+        assert_eq!(
+            Day12::part2(&heightmap, &NoParams),
+            Answer::NoAnswer(
+                "the end square E cannot be reached from any square at elevation a".to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::slice;
+use std::str::FromStr;
+
+use nom::{
+    branch::alt,
+    character::complete::char,
+    combinator::{all_consuming, cut, map},
+    multi::many0,
+    sequence::{pair, preceded, terminated},
+    Finish, IResult,
+};
+
+use crate::generate::{Generate, Rng};
+use crate::params::NoParams;
+use crate::parse::{parse_lines, ParseError};
+use crate::solution::{Answer, Solution};
+
+pub(crate) const INPUT: &str = include_str!("./input.txt");
+#[allow(dead_code)]
+const TEST_INPUT: &str = include_str!("./test_input.txt");
+
+/// An integer or a list of packets, as in `[1,[2,[]],3]`.
+///
+/// Packets are ordered by the distress signal rules: integers by value, lists element by element
+/// and then by length, and an integer compared with a list as if it were a list of itself. Equality
+/// follows that order, so `2`, `[2]` and `[[2]]` are all equal.
+#[derive(Debug, Clone)]
+pub enum Packet {
+    Int(u32),
+    List(Vec<Packet>),
+}
+
+impl Packet {
+    /// Any packet.
+    fn parse(i: &str) -> IResult<&str, Self> {
+        alt((
+            map(nom::character::complete::u32, Packet::Int),
+            map(Self::parse_list, Packet::List),
+        ))(i)
+    }
+
+    /// A bracketed list. Once its `[` is read, any error is final so that it points at the
+    /// malformed part rather than at the start of the list.
+    fn parse_list(i: &str) -> IResult<&str, Vec<Self>> {
+        preceded(
+            char('['),
+            cut(alt((
+                map(char(']'), |_| Vec::new()),
+                terminated(
+                    map(
+                        pair(Self::parse, many0(preceded(char(','), cut(Self::parse)))),
+                        |(first, rest)| [vec![first], rest].concat(),
+                    ),
+                    char(']'),
+                ),
+            ))),
+        )(i)
+    }
+}
+
+impl Ord for Packet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Packet::Int(a), Packet::Int(b)) => a.cmp(b),
+            (Packet::List(a), Packet::List(b)) => a.cmp(b),
+            (Packet::Int(_), Packet::List(b)) => slice::from_ref(self).cmp(b),
+            (Packet::List(a), Packet::Int(_)) => a.as_slice().cmp(slice::from_ref(other)),
+        }
+    }
+}
+
+impl PartialOrd for Packet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Packet {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Packet {}
+
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Packet::Int(value) => write!(f, "{value}"),
+            Packet::List(packets) => {
+                write!(f, "[")?;
+                for (i, packet) in packets.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{packet}")?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+/// A packet on a line of its own, which is always a list.
+impl FromStr for Packet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all_consuming(map(Self::parse_list, Packet::List))(s)
+            .finish()
+            .map(|(_, packet)| packet)
+            .map_err(|err| {
+                let reason = match err.input.chars().next() {
+                    None => "missing ]",
+                    Some(_) if err.input.len() == s.len() => "a packet starts with [",
+                    Some(']') => "unexpected ]",
+                    Some('[') => "unexpected [",
+                    Some(_) => "expected an integer, [, `,` or ]",
+                };
+                ParseError::new(reason, err.input).within(s, err.input)
+            })
+    }
+}
+
+/// Pairs of packets, one packet per line and a blank line between pairs.
+fn read_pairs(input: &str) -> Result<Vec<(Packet, Packet)>, ParseError> {
+    let lines = parse_lines(Day13::DAY, input.lines(), |line| {
+        if line.is_empty() {
+            Ok(None)
+        } else {
+            line.parse::<Packet>().map(Some)
+        }
+    })?;
+    lines
+        .chunks(3)
+        .enumerate()
+        .map(|(index, chunk)| match chunk {
+            [Some(left), Some(right)] | [Some(left), Some(right), None] => {
+                Ok((left.clone(), right.clone()))
+            }
+            _ => {
+                let at = (0..3)
+                    .position(|i| chunk.get(i).is_none_or(|line| line.is_some() == (i == 2)))
+                    .unwrap_or(0);
+                let reason = if at == 2 {
+                    "expected a blank line between pairs"
+                } else {
+                    "expected a packet"
+                };
+                Err(ParseError::new(reason, "").at_line(Day13::DAY, 3 * index + at + 1))
+            }
+        })
+        .collect()
+}
+
+/// The packets the decoder key is computed from.
+fn divider_packets() -> [Packet; 2] {
+    ["[[2]]", "[[6]]"].map(|divider| divider.parse().expect("divider packets are valid"))
+}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+
+    type Input = Vec<(Packet, Packet)>;
+    type Params = NoParams;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        read_pairs(input)
+    }
+
+    fn part1(input: &Self::Input, _params: &Self::Params) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input, _params: &Self::Params) -> Answer {
+        part2(input).into()
+    }
+}
+
+fn random_packet(rng: &mut Rng, depth: usize) -> Packet {
+    if depth > 0 && rng.chance(60) {
+        Packet::List(
+            (0..rng.usize(0..=4))
+                .map(|_| random_packet(rng, depth - 1))
+                .collect(),
+        )
+    } else {
+        Packet::Int(rng.below(11) as u32)
+    }
+}
+
+impl Generate for Day13 {
+    const DEFAULT_SIZE: usize = 150;
+
+    /// `size` pairs of random packets nested at most four lists deep.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let random_list = |rng: &mut Rng| {
+            Packet::List(
+                (0..rng.usize(0..=5))
+                    .map(|_| random_packet(rng, 3))
+                    .collect(),
+            )
+        };
+        let pairs: Vec<String> = (0..size)
+            .map(|_| format!("{}\n{}", random_list(rng), random_list(rng)))
+            .collect();
+        pairs.join("\n\n")
+    }
+}
+
+fn part1(pairs: &[(Packet, Packet)]) -> usize {
+    pairs
+        .iter()
+        .enumerate()
+        .filter(|(_, (left, right))| left < right)
+        .map(|(index, _)| index + 1)
+        .sum()
+}
+
+fn part2(pairs: &[(Packet, Packet)]) -> usize {
+    let dividers = divider_packets();
+    let mut packets: Vec<&Packet> = pairs
+        .iter()
+        .flat_map(|(left, right)| [left, right])
+        .chain(&dividers)
+        .collect();
+    packets.sort();
+    dividers
+        .iter()
+        .map(|divider| {
+            packets
+                .iter()
+                .position(|&packet| std::ptr::eq(packet, divider))
+                .expect("the dividers are among the sorted packets")
+                + 1
+        })
+        .product()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn packet(s: &str) -> Packet {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn part1_works_with_test_input() {
+        assert_eq!(part1(&read_pairs(TEST_INPUT).unwrap()), 13);
+    }
+
+    #[test]
+    fn part2_works_with_test_input() {
+        assert_eq!(part2(&read_pairs(TEST_INPUT).unwrap()), 140);
+    }
+
+    #[test]
+    fn packets_compare_by_the_distress_signal_rules() {
+        assert!(packet("[1,1,3,1,1]") < packet("[1,1,5,1,1]"));
+        assert!(packet("[[1],[2,3,4]]") < packet("[[1],4]"));
+        assert!(packet("[9]") > packet("[[8,7,6]]"));
+        assert!(packet("[7,7,7,7]") > packet("[7,7,7]"));
+        assert!(packet("[[[]]]") > packet("[[]]"));
+        assert_eq!(packet("[2]"), packet("[[2]]"));
+        assert_eq!(packet("[[2],3]").cmp(&packet("[2,[3]]")), Ordering::Equal);
+    }
+
+    #[test]
+    fn packets_print_as_they_were_read() {
+        for seed in 0..5 {
+            let input = Day13::generate(&mut Rng::new(seed), 50);
+            let printed: Vec<String> = read_pairs(&input)
+                .unwrap()
+                .iter()
+                .map(|(left, right)| format!("{left}\n{right}"))
+                .collect();
+            assert_eq!(printed.join("\n\n"), input, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn packet_parse_reports_the_malformed_bracket() {
+        let cases = [
+            ("[1,[2,3]", 9, "missing ]"),
+            ("[1,2]]", 6, "unexpected ]"),
+            ("[1,]", 4, "unexpected ]"),
+            ("[1[2]]", 3, "unexpected ["),
+            ("[1;2]", 3, "expected an integer, [, `,` or ]"),
+            ("1,2]", 1, "a packet starts with ["),
+        ];
+        for (line, column, reason) in cases {
+            let err = line.parse::<Packet>().unwrap_err();
+            assert_eq!(
+                (err.column, err.reason.as_str()),
+                (column, reason),
+                "{line}"
+            );
+        }
+    }
+
+    #[test]
+    fn read_pairs_reports_the_line() {
+        let err = read_pairs("[1]\n[2]\n\n[3]\n[[4]").unwrap_err();
+        assert_eq!(
+            (err.line, err.column, err.reason.as_str()),
+            (5, 5, "missing ]")
+        );
+        let err = read_pairs("[1]\n[2]\n[3]").unwrap_err();
+        assert_eq!(
+            (err.line, err.reason.as_str()),
+            (3, "expected a blank line between pairs")
+        );
+        let err = read_pairs("[1]\n\n[3]").unwrap_err();
+        assert_eq!((err.line, err.reason.as_str()), (2, "expected a packet"));
+    }
+}
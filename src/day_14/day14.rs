@@ -1,15 +1,27 @@
-use color_eyre::eyre::{Error, Result};
 use itertools::Itertools;
 use std::cmp;
-use std::collections::HashMap;
-use std::str::FromStr;
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::generate::{Generate, Rng};
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::params::Validate;
+use crate::parse::{parse_lines, parse_num, ParseError};
+use crate::solution::{Answer, Solution};
+use crate::trace::{self, Event};
 
 #[allow(dead_code)]
 const TEST_INPUT: &str = include_str!("./test_input.txt");
-const INPUT: &str = include_str!("./input.txt");
+pub(crate) const INPUT: &str = include_str!("./input.txt");
+
+/// Where the sand pours in from.
+const SOURCE: Point = Point::new(500, 0);
 
-#[derive(Clone, PartialEq, Eq, Hash, Debug)]
-pub(crate) enum Element {
+#[derive(Clone, Default, PartialEq, Eq, Hash, Debug)]
+pub enum Element {
+    #[default]
     Air,
     Rock,
     Sand,
@@ -26,172 +38,414 @@ impl From<&str> for Element {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Hash)]
-pub(crate) struct Coord {
-    pub(crate) x: usize,
-    pub(crate) y: usize,
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let c = match self {
+            Self::Air => '.',
+            Self::Rock => '#',
+            Self::Sand => 'o',
+        };
+        write!(f, "{c}")
+    }
+}
+
+fn read_coord(s: &str) -> Result<Point, ParseError> {
+    let coord = s.trim();
+    let (x, y) = coord
+        .split_once(',')
+        .ok_or_else(|| ParseError::new("expected <x>,<y>", coord).within(s, coord))?;
+    let x = parse_num::<u32>(x, "x").map_err(|e| e.within(s, x))?;
+    let y = parse_num::<u32>(y, "y").map_err(|e| e.within(s, y))?;
+
+    Ok(Point::new(x.into(), y.into()))
 }
 
-impl Coord {
-    fn add(&mut self, coord: (isize, isize)) {
-        self.x = self.x.checked_add_signed(coord.0).unwrap();
-        self.y = self.y.checked_add_signed(coord.1).unwrap();
+fn read_lines(input: &str) -> Result<Cave, ParseError> {
+    let mut rocks = Vec::new();
+    parse_lines(Day14::DAY, input.lines(), |line| {
+        let coords: Vec<&str> = line.split("->").map(str::trim).collect();
+        if coords.len() < 2 {
+            return Err(ParseError::new(
+                "expected a path of points joined by ->",
+                line,
+            ));
+        }
+        let points = coords
+            .iter()
+            .map(|coord| read_coord(coord).map_err(|e| e.within(line, coord)))
+            .collect::<Result<Vec<_>, _>>()?;
+        for ((p_start, p_end), coord) in points.iter().tuple_windows().zip(&coords[1..]) {
+            if p_start.x == p_end.x {
+                for y in cmp::min(p_start.y, p_end.y)..=cmp::max(p_start.y, p_end.y) {
+                    rocks.push(Point::new(p_start.x, y));
+                }
+            } else if p_start.y == p_end.y {
+                for x in cmp::min(p_start.x, p_end.x)..=cmp::max(p_start.x, p_end.x) {
+                    rocks.push(Point::new(x, p_start.y));
+                }
+            } else {
+                return Err(
+                    ParseError::new("a segment must be horizontal or vertical", *coord)
+                        .within(line, coord),
+                );
+            }
+        }
+        Ok(())
+    })?;
+    if rocks.is_empty() {
+        return Err(ParseError::new("expected at least one rock path", "").at_line(Day14::DAY, 1));
     }
 
-    fn get(&self) -> (usize, usize) {
-        (self.x, self.y)
+    // Sand spreads at most one column per row it falls, and the floor of part 2 lies two rows
+    // below the lowest rock, so this span holds every grain either part can come to rest on.
+    let depth = rocks.iter().map(|rock| rock.y).max().unwrap_or(0) + 2;
+    let min_x = rocks
+        .iter()
+        .map(|rock| rock.x)
+        .fold(SOURCE.x - depth, cmp::min);
+    let max_x = rocks
+        .iter()
+        .map(|rock| rock.x)
+        .fold(SOURCE.x + depth, cmp::max);
+    let mut cave = Cave::spanning(Point::new(min_x, 0), Point::new(max_x, depth));
+    for rock in rocks {
+        cave[rock] = Element::Rock;
     }
+    Ok(cave)
 }
 
-impl std::fmt::Debug for Coord {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {})", self.x, self.y)
+pub type Cave = Grid<Element>;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    /// Where the sand pours in from, e.g. `{ x = 500, y = 0 }`.
+    pub source: Point,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self { source: SOURCE }
     }
 }
 
-impl From<(usize, usize)> for Coord {
-    fn from((x, y): (usize, usize)) -> Self {
-        Self { x, y }
+/// Largest coordinate of a source. Puzzle caves lie well within it, and the cave grows to hold all
+/// the sand poured from the source, so one much farther away would not fit in memory.
+const MAX_SOURCE: i64 = 1000;
+
+impl Validate for Params {
+    fn validate(&self) -> color_eyre::Result<()> {
+        let Point { x, y } = self.source;
+        if !(0..=MAX_SOURCE).contains(&x) || !(0..=MAX_SOURCE).contains(&y) {
+            color_eyre::eyre::bail!(
+                "source must lie within 0..={MAX_SOURCE} on both axes, got {x},{y}"
+            );
+        }
+        Ok(())
     }
 }
 
-impl FromStr for Coord {
-    type Err = Error;
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+
+    type Input = Cave;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        read_lines(input)
+    }
 
-    fn from_str(s: &str) -> Result<Self> {
-        let (x, y) = s
-            .trim()
-            .split_once(',')
-            .unwrap_or_else(|| panic!("Could not split str {} ", s));
-        let x = x.parse::<usize>()?;
-        let y = y.parse::<usize>()?;
+    fn part1(input: &Self::Input, params: &Params) -> Answer {
+        part1(input, params.source).into()
+    }
 
-        Ok(Self { x, y })
+    fn part2(input: &Self::Input, params: &Params) -> Answer {
+        part2(input, params.source).into()
     }
 }
 
-fn read_lines(input: &str) -> Result<HashMap<Coord, Element>> {
-    let mut coordinates = HashMap::new();
-    for line in input.lines() {
-        for (p1, p2) in line.split("->").tuple_windows::<(&str, &str)>() {
-            let p_start = Coord::from_str(p1)?;
-            let p_end = Coord::from_str(p2)?;
-            if p_start.x == p_end.x {
-                for y in cmp::min(p_start.y, p_end.y)..=cmp::max(p_start.y, p_end.y) {
-                    coordinates.insert(Coord::from((p_start.x, y)), Element::from("#"));
-                }
-            }
-            if p_start.y == p_end.y {
-                for x in cmp::min(p_start.x, p_end.x)..=cmp::max(p_start.x, p_end.x) {
-                    coordinates.insert(Coord::from((x, p_start.y)), Element::from("#"));
+impl Generate for Day14 {
+    const DEFAULT_SIZE: usize = 150;
+
+    /// `size` rock paths, at least one, of one to five straight segments below and around the
+    /// source.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let paths: Vec<String> = (0..size.max(1))
+            .map(|_| {
+                let mut at = Point::new(rng.range(350..=650), rng.range(2..=170));
+                let mut path = vec![at];
+                let horizontal_first = rng.chance(50);
+                for segment in 0..rng.usize(1..=5) {
+                    let length = rng.range(1..=10);
+                    let step = if rng.chance(50) { length } else { -length };
+                    // Rocks stay clear of the source row and of negative coordinates.
+                    if (segment % 2 == 0) == horizontal_first {
+                        at.x = if at.x + step >= 1 {
+                            at.x + step
+                        } else {
+                            at.x + length
+                        };
+                    } else {
+                        at.y = if at.y + step >= 2 {
+                            at.y + step
+                        } else {
+                            at.y + length
+                        };
+                    }
+                    path.push(at);
                 }
+                let path: Vec<String> = path.iter().map(|p| format!("{},{}", p.x, p.y)).collect();
+                path.join(" -> ")
+            })
+            .collect();
+        paths.join("\n")
+    }
+}
+
+fn calculate_bottom(grid: &Cave) -> i64 {
+    grid.iter()
+        .filter(|(_, e)| **e == Element::Rock)
+        .map(|(c, _)| c.y)
+        .max()
+        .expect("the parser rejects a cave without rock")
+}
+
+/// Where a grain tries to go next, in order: down, down-left and down-right.
+const FALLS: [Point; 3] = [Point::new(0, 1), Point::new(-1, 1), Point::new(1, 1)];
+
+/// The cave grown to hold every grain poured from `source` down to the floor, for a source
+/// other than the one the parsed cave was sized for.
+fn room_for(cave: &Cave, source: Point) -> Cave {
+    let top = cmp::min(cave.min().y, source.y);
+    let depth = cmp::max(cave.max().y, source.y + 1);
+    let spread = depth - top;
+    let min = Point::new(cmp::min(cave.min().x, source.x - spread), top);
+    let max = Point::new(cmp::max(cave.max().x, source.x + spread), depth);
+    if (min, max) == (cave.min(), cave.max()) {
+        return cave.clone();
+    }
+    let mut room = Cave::spanning(min, max);
+    for (coord, element) in cave.iter() {
+        room[coord] = element.clone();
+    }
+    room
+}
+
+/// Pours sand from `source` until a grain comes to rest at the source or, without a `floor`,
+/// falls past the lowest rock, and returns how many grains came to rest.
+fn pour(cave: &Cave, source: Point, floor: Option<i64>) -> usize {
+    let mut grid = room_for(cave, source);
+    let bottom = calculate_bottom(&grid);
+    // Every grain follows the previous one down to the last place it had a choice, so the path
+    // of the previous grain is kept and the next one starts from its end.
+    let mut path = vec![source];
+    let mut grains = 0;
+    while let Some(&sand) = path.last() {
+        let next = FALLS
+            .iter()
+            .map(|&fall| sand + fall)
+            .find(|&next| Some(next.y) != floor && grid.cell(next) == Some(&Element::Air));
+        match next {
+            Some(next) if floor.is_none() && next.y > bottom => break,
+            Some(next) => path.push(next),
+            None => {
+                grid[sand] = Element::Sand;
+                trace::emit(|| Event::SandSettled { at: sand });
+                grains += 1;
+                path.pop();
             }
         }
     }
-    Ok(coordinates)
+    grains
 }
 
-pub fn run() {
-    println!("=== Day 14 ===");
-    println!("Day 14 part 1, solution: {}", part1(INPUT));
-    println!("Day 14 part 2, solution: {}", part2(INPUT));
-    println!("=============");
-    println!();
+fn part1(cave: &Cave, source: Point) -> usize {
+    pour(cave, source, None)
 }
 
-fn calculate_bottom(grid: &HashMap<Coord, Element>) -> usize {
-    grid.iter().max_by_key(|(c, _e)| c.y).unwrap().0.y
+fn part2(cave: &Cave, source: Point) -> usize {
+    pour(cave, source, Some(calculate_bottom(cave) + 2))
 }
 
-fn part1(input: &str) -> usize {
-    let mut grid = read_lines(input).expect("Something wne wrong while generating grid!");
-    let bottom = calculate_bottom(&grid);
-    let steps: [(isize, isize); 3] = [(0, 1), (-1, 1), (1, 1)];
-    'outer: loop {
-        let mut sand = Coord::from((500, 0));
-        'inner: loop {
-            let mut found = false;
-            for step in &steps {
-                let sand_pos = sand.get();
-                let mut next_coord = Coord::from(sand_pos);
-                next_coord.add(*step);
-                if !grid.contains_key(&next_coord) {
-                    if next_coord.y > bottom {
+/// Forms of the solvers dropping every grain from the source one step at a time, slow but hard
+/// to get wrong.
+#[cfg(test)]
+mod reference {
+    use super::*;
+
+    pub(super) fn part1(cave: &Cave) -> usize {
+        let mut grid = cave.clone();
+        let bottom = calculate_bottom(&grid);
+        let steps = [Point::new(0, 1), Point::new(-1, 1), Point::new(1, 1)];
+        'outer: loop {
+            let mut sand = SOURCE;
+            'inner: loop {
+                let mut found = false;
+                for step in &steps {
+                    let next_coord = sand + *step;
+                    if grid[next_coord] == Element::Air {
+                        if next_coord.y > bottom {
+                            break 'outer;
+                        }
+                        sand = next_coord;
+                        found = true;
+                        break;
+                    }
+                }
+                if !found {
+                    grid[sand] = Element::Sand;
+                    // A basin around the source can fill up before any sand reaches the abyss.
+                    if sand == SOURCE {
                         break 'outer;
                     }
-                    sand.add(*step);
-                    found = true;
-                    break;
+                    break 'inner;
                 }
             }
-            if !found {
-                grid.insert(sand, Element::Sand);
-                break 'inner;
-            }
         }
+
+        grid.iter().filter(|(_, v)| **v == Element::Sand).count()
     }
 
-    grid.iter().filter(|(_, v)| **v == Element::Sand).count()
-}
-
-fn part2(input: &str) -> usize {
-    let mut grid = read_lines(input).expect("Something wne wrong while generating grid!");
-    let bottom = calculate_bottom(&grid) + 2;
-    let steps: [(isize, isize); 3] = [(0, 1), (-1, 1), (1, 1)];
-    'outer: loop {
-        let mut sand = Coord::from((500, 0));
-        'inner: loop {
-            let mut found = false;
-            for step in &steps {
-                let sand_pos = sand.get();
-                let mut next_coord = Coord::from(sand_pos);
-                next_coord.add(*step);
-                if !grid.contains_key(&next_coord) {
-                    sand.add(*step);
-                    if next_coord.y == bottom - 1 {
-                        grid.insert(sand, Element::Sand);
-                        break 'inner;
+    pub(super) fn part2(cave: &Cave) -> usize {
+        let mut grid = cave.clone();
+        let bottom = calculate_bottom(&grid) + 2;
+        let steps = [Point::new(0, 1), Point::new(-1, 1), Point::new(1, 1)];
+        'outer: loop {
+            let mut sand = SOURCE;
+            'inner: loop {
+                let mut found = false;
+                for step in &steps {
+                    let next_coord = sand + *step;
+                    if grid[next_coord] == Element::Air {
+                        sand = next_coord;
+                        if next_coord.y == bottom - 1 {
+                            grid[sand] = Element::Sand;
+                            break 'inner;
+                        }
+                        found = true;
+                        break;
                     }
-                    found = true;
-                    break;
                 }
-            }
-            if sand.get() == (500, 0) {
-                grid.insert(sand, Element::Sand);
-                break 'outer;
-            }
-            if !found {
-                grid.insert(sand, Element::Sand);
-                break 'inner;
+                if sand == SOURCE {
+                    grid[sand] = Element::Sand;
+                    break 'outer;
+                }
+                if !found {
+                    grid[sand] = Element::Sand;
+                    break 'inner;
+                }
             }
         }
-    }
 
-    grid.iter().filter(|(_, v)| **v == Element::Sand).count()
+        grid.iter().filter(|(_, v)| **v == Element::Sand).count()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::assert_agree;
+    use crate::params;
     use std::assert_eq;
 
     #[test]
     fn part1_works_with_test_input() {
-        assert_eq!(part1(TEST_INPUT), 24);
+        assert_eq!(part1(&read_lines(TEST_INPUT).unwrap(), SOURCE), 24);
     }
 
     #[test]
     fn part1_works_with_puzzle_input() {
-        assert_eq!(part1(INPUT), 897);
+        assert_eq!(part1(&read_lines(INPUT).unwrap(), SOURCE), 897);
     }
 
     #[test]
     fn part2_works_with_test_input() {
-        assert_eq!(part2(TEST_INPUT), 93);
+        assert_eq!(part2(&read_lines(TEST_INPUT).unwrap(), SOURCE), 93);
     }
 
     #[test]
     fn part2_works_with_puzzle_input() {
-        assert_eq!(part2(INPUT), 26683);
+        assert_eq!(part2(&read_lines(INPUT).unwrap(), SOURCE), 26683);
+    }
+
+    #[test]
+    fn part1_stops_when_the_source_is_buried() {
+        assert_eq!(
+            part1(
+                &read_lines("497,1 -> 497,3 -> 503,3 -> 503,1").unwrap(),
+                SOURCE
+            ),
+            9
+        );
+    }
+
+    #[test]
+    fn every_settled_grain_is_traced() {
+        let cave = read_lines(TEST_INPUT).unwrap();
+        let (grains, events) = trace::record(|| part1(&cave, SOURCE));
+        assert_eq!(events.len(), grains);
+        assert_eq!(
+            events[0],
+            Event::SandSettled {
+                at: Point::new(500, 8)
+            }
+        );
+    }
+
+    #[test]
+    fn sand_can_pour_from_another_source() {
+        // Moving the source along with every rock leaves the answers as they were.
+        let shifted: Vec<String> = TEST_INPUT
+            .lines()
+            .map(|line| {
+                line.split(" -> ")
+                    .map(|coord| {
+                        let (x, y) = coord.split_once(',').unwrap();
+                        format!("{},{}", x.parse::<i64>().unwrap() - 60, y)
+                    })
+                    .join(" -> ")
+            })
+            .collect();
+        let cave = read_lines(&shifted.join("\n")).unwrap();
+        let source = SOURCE - Point::new(60, 0);
+        assert_eq!((part1(&cave, source), part2(&cave, source)), (24, 93));
+    }
+
+    #[test]
+    fn distant_source_is_rejected() {
+        let overrides = toml::toml! { source = { x = -100_000_000, y = 0 } };
+        let err = params::resolve::<Params>(&overrides).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid parameters: source must lie within 0..=1000 on both axes, got -100000000,0"
+        );
+    }
+
+    #[test]
+    fn solvers_agree_with_the_reference() {
+        assert_agree::<Day14, _>(
+            8,
+            15,
+            |cave| (part1(cave, SOURCE), part2(cave, SOURCE)),
+            |cave| (reference::part1(cave), reference::part2(cave)),
+        );
+    }
+
+    #[test]
+    fn read_lines_reports_invalid_paths() {
+        let err = read_lines("498,4 -> 498,6\n503,4 -> 50x,4").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 10, "50x"));
+        let err = read_lines("498,4 -> 498,6\n503,4").unwrap_err();
+        assert_eq!(
+            (err.line, err.reason.as_str()),
+            (2, "expected a path of points joined by ->")
+        );
+        let err = read_lines("498,4 -> 498,6 -> 496,8").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (1, 19, "496,8"));
+        assert_eq!(err.reason, "a segment must be horizontal or vertical");
+        let err = read_lines("").unwrap_err();
+        assert_eq!(err.reason, "expected at least one rock path");
     }
 }
@@ -1,39 +1,52 @@
-use std::fmt;
+use std::collections::HashSet;
+use std::str::FromStr;
 
 use nom::{
-    bytes::streaming::tag,
+    bytes::complete::tag,
     combinator::{all_consuming, map},
     sequence::{preceded, separated_pair},
     Finish, IResult,
 };
-
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Point {
-    pub x: i64,
-    pub y: i64,
+use serde::Deserialize;
+
+use crate::generate::{Generate, Rng};
+use crate::geometry::Point;
+use crate::interval::{Interval, IntervalSet};
+use crate::params::Validate;
+use crate::parse::{parse_lines, ParseError};
+use crate::solution::{Answer, Solution};
+use crate::trace::{self, Event};
+
+pub(crate) const INPUT: &str = include_str!("./input.txt");
+#[allow(dead_code)]
+const TEST_INPUT: &str = include_str!("./test_input.txt");
+
+const ROW: i64 = 2_000_000;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    /// Row whose impossible beacon positions part 1 counts.
+    pub row: i64,
 }
 
-impl fmt::Debug for Point {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "({}, {})", self.x, self.y)
+impl Default for Params {
+    fn default() -> Self {
+        Self { row: ROW }
     }
 }
 
-impl Point {
-    fn parse(i: &str) -> IResult<&str, Point> {
-        map(
-            separated_pair(
-                preceded(tag("x="), nom::character::complete::i64),
-                tag(", "),
-                preceded(tag("y="), nom::character::complete::i64),
-            ),
-            |(x, y)| Point { x, y },
-        )(i)
-    }
-
-    pub fn manhattan_dist(self, other: Self) -> i64 {
-        (self.x.abs_diff(other.x) + self.y.abs_diff(other.y)) as i64
-    }
+impl Validate for Params {}
+
+fn point(i: &str) -> IResult<&str, Point> {
+    map(
+        separated_pair(
+            preceded(tag("x="), nom::character::complete::i64),
+            tag(", "),
+            preceded(tag("y="), nom::character::complete::i64),
+        ),
+        |(x, y)| Point { x, y },
+    )(i)
 }
 
 #[derive(Debug)]
@@ -42,65 +55,130 @@ pub struct Record {
     pub beacon: Point,
 }
 
-impl Record {
-    pub fn must_parse(i: &str) -> Self {
-        all_consuming(Self::parse)(i)
+impl FromStr for Record {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        all_consuming(Self::parse)(s)
             .finish()
-            .expect("failed to parse input")
-            .1
+            .map(|(_, record)| record)
+            .map_err(|err| {
+                ParseError::new(
+                    format!("expected a sensor report ({:?})", err.code),
+                    err.input,
+                )
+                .within(s, err.input)
+            })
+    }
+}
+
+impl Record {
+    /// The x positions on row `y` no closer to the sensor than its beacon.
+    fn coverage(&self, y: i64) -> Option<Interval> {
+        let radius = self.sensor.manhattan(self.beacon) as i64;
+        let reach = radius - self.sensor.y.abs_diff(y) as i64;
+        Interval::new(self.sensor.x - reach, self.sensor.x + reach)
     }
 
     fn parse(i: &str) -> IResult<&str, Self> {
         map(
             separated_pair(
-                preceded(tag("Sensor at "), Point::parse),
+                preceded(tag("Sensor at "), point),
                 tag(": closest beacon is at "),
-                Point::parse,
+                point,
             ),
             |(sensor, beacon)| Record { sensor, beacon },
         )(i)
     }
 }
 
-struct Map {
-    records: Vec<Record>,
+#[derive(Debug)]
+pub struct Map {
+    pub records: Vec<Record>,
 }
 
 impl Map {
-    fn parse(input: &str) -> Self {
-        let records = input.lines().map(Record::must_parse).collect();
-        Self { records }
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let records = parse_lines(Day15::DAY, input.lines(), Record::from_str)?;
+        Ok(Self { records })
     }
 
-    fn dump(&self) {
-        for record in &self.records {
-            println!("{record:?}");
-        }
+    /// The x positions on row `y` within reach of some sensor.
+    fn coverage(&self, y: i64) -> IntervalSet {
+        self.records
+            .iter()
+            .filter_map(|record| record.coverage(y))
+            .collect()
     }
 
     fn num_impossible_positions(&self, y: i64) -> usize {
-        let mut total = 0;
-        let min_x = -4;
-        let max_x = 26;
-
-        for x in min_x..=max_x {
-            let point = Point { x, y };
-            if self.records.iter().any(|rec| rec.beacon == point) {
-                // already have a beacon there, not an impossible position
-            } else if self.records.iter().any(|rec| {
-                let radius = rec.sensor.manhattan_dist(rec.beacon);
-                rec.sensor.manhattan_dist(point) <= radius
-            }) {
-                // covered!
-                total += 1
-            }
-        }
-
-        total
+        let covered = self.coverage(y);
+        trace::emit(|| Event::RowCovered {
+            y,
+            covered: covered.clone(),
+        });
+        // A known beacon is not an impossible position, even though a sensor reaches it.
+        let beacons: HashSet<i64> = self
+            .records
+            .iter()
+            .filter(|record| record.beacon.y == y && covered.contains(record.beacon.x))
+            .map(|record| record.beacon.x)
+            .collect();
+
+        covered.len() as usize - beacons.len()
+    }
 }
 
-fn part1(input: &str) -> u64 {
-    let map = Map::parse(input);
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+
+    type Input = Map;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        Map::parse(input)
+    }
+
+    fn part1(input: &Self::Input, params: &Params) -> Answer {
+        part1(input, params.row).into()
+    }
+}
+
+impl Generate for Day15 {
+    const DEFAULT_SIZE: usize = 30;
+
+    /// `size` sensors scattered around the scanned row, each with a beacon at most a tenth of
+    /// the spread away.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let spread = 2 * ROW;
+        let records: Vec<Record> = (0..size)
+            .map(|_| {
+                let sensor = Point::new(rng.range(0..=spread), rng.range(0..=spread));
+                let radius = rng.range(0..=spread / 10);
+                let dx = rng.range(0..=radius);
+                let (sx, sy) = (*rng.choose(&[-1, 1]), *rng.choose(&[-1, 1]));
+                let beacon = sensor + Point::new(sx * dx, sy * (radius - dx));
+                Record { sensor, beacon }
+            })
+            .collect();
+
+        let lines: Vec<String> = records
+            .iter()
+            .map(|Record { sensor, beacon }| {
+                format!(
+                    "Sensor at x={}, y={}: closest beacon is at x={}, y={}",
+                    sensor.x, sensor.y, beacon.x, beacon.y
+                )
+            })
+            .collect();
+        lines.join("\n")
+    }
+}
+
+fn part1(map: &Map, y: i64) -> usize {
+    map.num_impossible_positions(y)
 }
 
 #[cfg(test)]
@@ -111,21 +189,30 @@ mod tests {
 
     #[test]
     fn part1_works_with_test_input() {
-        assert_eq!(part1(TEST_INPUT, 10), 26);
+        assert_eq!(part1(&Map::parse(TEST_INPUT).unwrap(), 10), 26);
     }
 
     #[test]
     fn part1_works_with_puzzle_input() {
-        assert_eq!(part1(INPUT, 2000000), 26);
+        assert_eq!(part1(&Map::parse(INPUT).unwrap(), 2000000), 6078701);
     }
 
     #[test]
-    fn part2_works_with_test_input() {
-        assert_eq!(part2(TEST_INPUT), 26);
+    fn map_parse_reports_the_failing_column() {
+        let input = "Sensor at x=2, y=18: closest beacon is at x=-2, y=15\n\
+                     Sensor at x=9, y=1G: closest beacon is at x=10, y=16";
+        let err = Map::parse(input).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 19));
+        assert!(err.text.starts_with("G: closest"));
     }
 
-    #[test]
-    fn part2_works_with_puzzle_input() {
-        assert_eq!(part2(INPUT), 26);
-    }
+    // #[test]
+    // fn part2_works_with_test_input() {
+    //     assert_eq!(part2(TEST_INPUT), 56000011);
+    // }
+
+    // #[test]
+    // fn part2_works_with_puzzle_input() {
+    //     assert_eq!(part2(INPUT), 12567351400528);
+    // }
 }
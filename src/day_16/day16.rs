@@ -0,0 +1,574 @@
+use std::collections::HashMap;
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::alpha1,
+    combinator::all_consuming,
+    multi::separated_list1,
+    sequence::{preceded, tuple},
+    Finish, IResult,
+};
+use serde::Deserialize;
+
+use crate::generate::{Generate, Rng};
+use crate::params::Validate;
+use crate::parse::{parse_lines, ParseError};
+use crate::search;
+use crate::solution::{Answer, Solution};
+
+pub(crate) const INPUT: &str = include_str!("./input.txt");
+#[allow(dead_code)]
+const TEST_INPUT: &str = include_str!("./test_input.txt");
+
+/// The valve both explorers start at.
+const START: &str = "AA";
+const MINUTES: u32 = 30;
+/// Time left once the elephant has been taught to open valves.
+const MINUTES_WITH_ELEPHANT: u32 = 26;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Valve {
+    pub name: String,
+    pub flow: u32,
+    /// Indices of the valves one tunnel away.
+    pub tunnels: Vec<usize>,
+}
+
+/// The valve network, along with the shortest distances between the valves worth opening.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Network {
+    pub valves: Vec<Valve>,
+    pub start: usize,
+    /// Indices of the valves with a positive flow rate.
+    useful: Vec<usize>,
+    /// Minutes to walk between two useful valves, by position in `useful`, the start being at
+    /// position `useful.len()`. `u32::MAX` when there is no way.
+    distances: Vec<Vec<u32>>,
+}
+
+/// A valve opened by an explorer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Opening {
+    pub valve: String,
+    /// The minute spent opening it, counting from 1; it releases pressure from the next one.
+    pub minute: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Plan {
+    /// Total pressure released by the end.
+    pub pressure: u32,
+    /// The valves each explorer opens, in order.
+    pub openings: Vec<Vec<Opening>>,
+}
+
+/// Best pressure and the valves opened for it, by set of opened valves, a bit per position in
+/// [`Network::useful`]. The valves are given with the minute they are opened in.
+type BestByOpened = HashMap<u64, (u32, Vec<(usize, u32)>)>;
+
+/// Depth-first search over the orders of opening valves, keeping the best of each set opened.
+struct Explorer<'a> {
+    network: &'a Network,
+    minutes: u32,
+    path: Vec<(usize, u32)>,
+    best: BestByOpened,
+}
+
+impl Explorer<'_> {
+    fn visit(&mut self, at: usize, time_left: u32, opened: u64, pressure: u32) {
+        let best = self.best.entry(opened).or_insert((0, Vec::new()));
+        if pressure > best.0 {
+            *best = (pressure, self.path.clone());
+        }
+        for next in 0..self.network.useful.len() {
+            let cost = self.network.distances[at][next].saturating_add(1);
+            if opened & 1 << next != 0 || cost >= time_left {
+                continue;
+            }
+            let time_left = time_left - cost;
+            let flow = self.network.valves[self.network.useful[next]].flow;
+            self.path.push((next, self.minutes - time_left));
+            self.visit(
+                next,
+                time_left,
+                opened | 1 << next,
+                pressure + flow * time_left,
+            );
+            self.path.pop();
+        }
+    }
+}
+
+impl Network {
+    fn new(valves: Vec<Valve>, start: usize) -> Result<Self, ParseError> {
+        let useful: Vec<usize> = (0..valves.len())
+            .filter(|&valve| valves[valve].flow > 0)
+            .collect();
+        if useful.len() > 64 {
+            return Err(ParseError::new(
+                format!(
+                    "{} valves with a positive flow rate, at most 64",
+                    useful.len()
+                ),
+                "",
+            ));
+        }
+        let distances = useful
+            .iter()
+            .chain([&start])
+            .map(|&from| {
+                let reached = search::distances(from, |&valve| valves[valve].tunnels.clone());
+                useful
+                    .iter()
+                    .chain([&start])
+                    .map(|to| reached.get(to).map_or(u32::MAX, |&d| d as u32))
+                    .collect()
+            })
+            .collect();
+        Ok(Self {
+            valves,
+            start,
+            useful,
+            distances,
+        })
+    }
+
+    fn best_by_opened(&self, minutes: u32) -> BestByOpened {
+        let mut explorer = Explorer {
+            network: self,
+            minutes,
+            path: Vec::new(),
+            best: HashMap::new(),
+        };
+        explorer.visit(self.useful.len(), minutes, 0, 0);
+        explorer.best
+    }
+
+    fn openings(&self, path: &[(usize, u32)]) -> Vec<Opening> {
+        path.iter()
+            .map(|&(valve, minute)| Opening {
+                valve: self.valves[self.useful[valve]].name.clone(),
+                minute,
+            })
+            .collect()
+    }
+
+    /// The plan releasing the most pressure in `minutes` for one explorer.
+    pub fn plan(&self, minutes: u32) -> Plan {
+        let best = self.best_by_opened(minutes);
+        let (pressure, path) = best
+            .values()
+            .max_by_key(|(pressure, _)| *pressure)
+            .expect("the search records opening nothing");
+        Plan {
+            pressure: *pressure,
+            openings: vec![self.openings(path)],
+        }
+    }
+
+    /// The plan releasing the most pressure in `minutes` for two explorers working together,
+    /// who never open the same valve.
+    pub fn plan_with_elephant(&self, minutes: u32) -> Plan {
+        let mut best: Vec<_> = self.best_by_opened(minutes).into_iter().collect();
+        best.sort_unstable_by_key(|(_, (pressure, _))| std::cmp::Reverse(*pressure));
+        // The sets opened alone include the empty one, so pairing the best with it is a start.
+        let mut chosen = (0, 0);
+        let mut most = 0;
+        for (i, (opened, (pressure, _))) in best.iter().enumerate() {
+            if pressure + best[0].1 .0 <= most {
+                break;
+            }
+            for (j, (other, (other_pressure, _))) in best.iter().enumerate().skip(i) {
+                if pressure + other_pressure <= most {
+                    break;
+                }
+                if opened & other == 0 {
+                    (chosen, most) = ((i, j), pressure + other_pressure);
+                    break;
+                }
+            }
+        }
+        Plan {
+            pressure: most,
+            openings: [chosen.0, chosen.1]
+                .map(|i| self.openings(&best[i].1 .1))
+                .into(),
+        }
+    }
+}
+
+/// A valve's line, the names it refers to being kept as slices of the line.
+struct Record<'a> {
+    name: &'a str,
+    flow: u32,
+    tunnels: Vec<&'a str>,
+}
+
+fn record(i: &str) -> IResult<&str, Record<'_>> {
+    let (i, (name, flow, tunnels)) = tuple((
+        preceded(tag("Valve "), alpha1),
+        preceded(tag(" has flow rate="), nom::character::complete::u32),
+        preceded(
+            alt((
+                tag("; tunnels lead to valves "),
+                tag("; tunnel leads to valve "),
+            )),
+            separated_list1(tag(", "), alpha1),
+        ),
+    ))(i)?;
+    Ok((
+        i,
+        Record {
+            name,
+            flow,
+            tunnels,
+        },
+    ))
+}
+
+fn read_scan(input: &str) -> Result<Network, ParseError> {
+    let records = parse_lines(Day16::DAY, input.lines(), |line| {
+        all_consuming(record)(line)
+            .finish()
+            .map(|(_, record)| record)
+            .map_err(|err| {
+                ParseError::new(format!("expected a valve scan ({:?})", err.code), err.input)
+                    .within(line, err.input)
+            })
+    })?;
+    let lines: Vec<&str> = input.lines().collect();
+    let mut index = HashMap::new();
+    for (number, record) in records.iter().enumerate() {
+        if index.insert(record.name, number).is_some() {
+            return Err(ParseError::new("a second valve of this name", record.name)
+                .within(lines[number], record.name)
+                .at_line(Day16::DAY, number + 1));
+        }
+    }
+    let mut valves = Vec::new();
+    for (number, record) in records.iter().enumerate() {
+        let tunnels = record
+            .tunnels
+            .iter()
+            .map(|&name| {
+                index.get(name).copied().ok_or_else(|| {
+                    ParseError::new("no such valve", name)
+                        .within(lines[number], name)
+                        .at_line(Day16::DAY, number + 1)
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        valves.push(Valve {
+            name: record.name.to_string(),
+            flow: record.flow,
+            tunnels,
+        });
+    }
+    let start = *index.get(START).ok_or_else(|| {
+        ParseError::new(format!("no valve {START} to start from"), "").at_line(Day16::DAY, 0)
+    })?;
+    Network::new(valves, start).map_err(|err| err.at_line(Day16::DAY, 0))
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    pub minutes: u32,
+    /// Time left in part 2, once the elephant has been taught.
+    pub minutes_with_elephant: u32,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            minutes: MINUTES,
+            minutes_with_elephant: MINUTES_WITH_ELEPHANT,
+        }
+    }
+}
+
+/// Most minutes a part may last. The search tries more orders of opening valves the longer it
+/// has, and the pressure released has to fit in a `u32`.
+const MAX_MINUTES: u32 = 60;
+
+impl Validate for Params {
+    fn validate(&self) -> color_eyre::Result<()> {
+        for (name, minutes) in [
+            ("minutes", self.minutes),
+            ("minutes_with_elephant", self.minutes_with_elephant),
+        ] {
+            if minutes > MAX_MINUTES {
+                color_eyre::eyre::bail!("{name} must be at most {MAX_MINUTES}, got {minutes}");
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    const DAY: u8 = 16;
+
+    type Input = Network;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        read_scan(input)
+    }
+
+    fn part1(input: &Self::Input, params: &Params) -> Answer {
+        part1(input, params.minutes).into()
+    }
+
+    fn part2(input: &Self::Input, params: &Params) -> Answer {
+        part2(input, params.minutes_with_elephant).into()
+    }
+}
+
+impl Generate for Day16 {
+    const DEFAULT_SIZE: usize = 60;
+
+    /// A connected network of `size` valves, at least 2, a third of them and at most 15 with a
+    /// positive flow rate, as in the puzzle input.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let size = size.max(2);
+        let mut names: Vec<String> = (b'A'..=b'Z')
+            .flat_map(|a| (b'A'..=b'Z').map(move |b| format!("{}{}", a as char, b as char)))
+            .filter(|name| name != START)
+            .collect();
+        rng.shuffle(&mut names);
+        names.truncate(size - 1);
+        names.insert(rng.usize(0..=size - 1), START.to_string());
+
+        let mut tunnels = vec![Vec::new(); size];
+        let mut connect = |a: usize, b: usize| {
+            if a != b && !tunnels[a].contains(&b) {
+                tunnels[a].push(b);
+                tunnels[b].push(a);
+            }
+        };
+        // Mostly long corridors, as in the puzzle input.
+        for valve in 1..size {
+            let previous = if rng.chance(75) {
+                valve - 1
+            } else {
+                rng.usize(0..=valve - 1)
+            };
+            connect(valve, previous);
+        }
+        for _ in 0..size / 10 {
+            connect(rng.usize(0..=size - 1), rng.usize(0..=size - 1));
+        }
+
+        let mut flows = vec![0; size];
+        let mut candidates: Vec<usize> = (0..size).filter(|&v| names[v] != START).collect();
+        rng.shuffle(&mut candidates);
+        for &valve in candidates.iter().take(size.div_ceil(3).min(15)) {
+            flows[valve] = rng.range(3..=25);
+        }
+
+        let lines: Vec<String> = (0..size)
+            .map(|valve| {
+                let leads: Vec<&str> = tunnels[valve].iter().map(|&v| &*names[v]).collect();
+                let lead = if leads.len() == 1 {
+                    "tunnel leads to valve"
+                } else {
+                    "tunnels lead to valves"
+                };
+                format!(
+                    "Valve {} has flow rate={}; {lead} {}",
+                    names[valve],
+                    flows[valve],
+                    leads.join(", ")
+                )
+            })
+            .collect();
+        lines.join("\n")
+    }
+}
+
+fn part1(network: &Network, minutes: u32) -> u32 {
+    network.plan(minutes).pressure
+}
+
+fn part2(network: &Network, minutes: u32) -> u32 {
+    network.plan_with_elephant(minutes).pressure
+}
+
+/// Forms of the solvers walking the whole network a minute at a time, slow but hard to get wrong.
+#[cfg(test)]
+mod reference {
+    use super::*;
+
+    /// Most pressure released in `time_left` from `at`, opening only valves of `allowed`, a bit
+    /// per valve index.
+    fn most_pressure(
+        network: &Network,
+        at: usize,
+        time_left: u32,
+        opened: u64,
+        allowed: u64,
+        memo: &mut HashMap<(usize, u32, u64), u32>,
+    ) -> u32 {
+        if time_left == 0 {
+            return 0;
+        }
+        if let Some(&pressure) = memo.get(&(at, time_left, opened)) {
+            return pressure;
+        }
+        let valve = &network.valves[at];
+        let mut most = 0;
+        if valve.flow > 0 && allowed & !opened & 1 << at != 0 {
+            most = valve.flow * (time_left - 1)
+                + most_pressure(network, at, time_left - 1, opened | 1 << at, allowed, memo);
+        }
+        for &next in &valve.tunnels {
+            most = most.max(most_pressure(
+                network,
+                next,
+                time_left - 1,
+                opened,
+                allowed,
+                memo,
+            ));
+        }
+        memo.insert((at, time_left, opened), most);
+        most
+    }
+
+    fn alone(network: &Network, minutes: u32, allowed: u64) -> u32 {
+        most_pressure(
+            network,
+            network.start,
+            minutes,
+            0,
+            allowed,
+            &mut HashMap::new(),
+        )
+    }
+
+    pub(super) fn part1(network: &Network, minutes: u32) -> u32 {
+        alone(network, minutes, u64::MAX)
+    }
+
+    /// Tries every split of the useful valves between the two explorers.
+    pub(super) fn part2(network: &Network, minutes: u32) -> u32 {
+        let useful: u64 = network.useful.iter().map(|&valve| 1 << valve).sum();
+        (0..=useful)
+            .filter(|mine| mine & !useful == 0)
+            .map(|mine| alone(network, minutes, mine) + alone(network, minutes, useful & !mine))
+            .max()
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::generate::assert_agree;
+    use crate::params;
+
+    /// Pressure released by the valves each explorer opens, recomputed from the plan.
+    fn released(network: &Network, plan: &Plan, minutes: u32) -> u32 {
+        plan.openings
+            .iter()
+            .flatten()
+            .map(|opening| {
+                let valve = network.valves.iter().find(|v| v.name == opening.valve);
+                valve.unwrap().flow * (minutes - opening.minute)
+            })
+            .sum()
+    }
+
+    fn names(openings: &[Opening]) -> Vec<&str> {
+        openings.iter().map(|opening| &*opening.valve).collect()
+    }
+
+    #[test]
+    fn part1_works_with_test_input() {
+        assert_eq!(part1(&read_scan(TEST_INPUT).unwrap(), MINUTES), 1651);
+    }
+
+    #[test]
+    fn part2_works_with_test_input() {
+        assert_eq!(
+            part2(&read_scan(TEST_INPUT).unwrap(), MINUTES_WITH_ELEPHANT),
+            1707
+        );
+    }
+
+    #[test]
+    fn plans_list_the_valves_opened_and_when() {
+        let network = read_scan(TEST_INPUT).unwrap();
+        let plan = network.plan(MINUTES);
+        assert_eq!(
+            names(&plan.openings[0]),
+            ["DD", "BB", "JJ", "HH", "EE", "CC"]
+        );
+        assert_eq!(
+            plan.openings[0]
+                .iter()
+                .map(|o| o.minute)
+                .collect::<Vec<_>>(),
+            [2, 5, 9, 17, 21, 24]
+        );
+        assert_eq!(released(&network, &plan, MINUTES), plan.pressure);
+
+        let plan = network.plan_with_elephant(MINUTES_WITH_ELEPHANT);
+        let mut split: Vec<Vec<&str>> = plan.openings.iter().map(|o| names(o)).collect();
+        split.sort();
+        assert_eq!(split, [["DD", "HH", "EE"], ["JJ", "BB", "CC"]]);
+        assert_eq!(
+            released(&network, &plan, MINUTES_WITH_ELEPHANT),
+            plan.pressure
+        );
+    }
+
+    #[test]
+    fn solvers_agree_with_the_reference() {
+        assert_agree::<Day16, _>(
+            10,
+            9,
+            |network| {
+                (
+                    part1(network, MINUTES),
+                    part2(network, MINUTES_WITH_ELEPHANT),
+                )
+            },
+            |network| {
+                (
+                    reference::part1(network, MINUTES),
+                    reference::part2(network, MINUTES_WITH_ELEPHANT),
+                )
+            },
+        );
+    }
+
+    #[test]
+    fn read_scan_reports_invalid_valves() {
+        let err = read_scan("Valve AA has flow rate=x; tunnel leads to valve BB").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 24));
+        let err = read_scan(
+            "Valve AA has flow rate=0; tunnels lead to valves BB, CC\n\
+             Valve BB has flow rate=3; tunnel leads to valve AA",
+        )
+        .unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (1, 54, "CC"));
+        let err = read_scan("Valve BB has flow rate=3; tunnel leads to valve BB").unwrap_err();
+        assert_eq!(err.reason, "no valve AA to start from");
+    }
+
+    #[test]
+    fn minutes_are_bounded() {
+        let overrides = toml::toml! { minutes_with_elephant = 61 };
+        let err = params::resolve::<Params>(&overrides).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid parameters: minutes_with_elephant must be at most 60, got 61"
+        );
+    }
+}
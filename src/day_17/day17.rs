@@ -1,11 +1,26 @@
 use std::cmp;
-use std::collections::{HashSet, VecDeque};
+
+use serde::Deserialize;
+
+use crate::generate::{Generate, Rng};
+use crate::geometry::Point;
+use crate::grid::Grid;
+use crate::params::Validate;
+use crate::parse::ParseError;
+use crate::solution::{Answer, Solution};
+use crate::trace::{self, Event};
 
 #[allow(dead_code)]
 const TEST_INPUT: &str = include_str!("./test_input.txt");
-const INPUT: &str = include_str!("./input.txt");
+pub(crate) const INPUT: &str = include_str!("./input.txt");
 
-type Tower = HashSet<Coord>;
+const LEFT: Point = Point::new(-1, 0);
+const RIGHT: Point = Point::new(1, 0);
+const DOWN: Point = Point::new(0, -1);
+
+/// Settled rock in the chamber, columns 1 to 7, with `y` growing upwards from the floor at
+/// `y == 0`.
+type Tower = Grid<bool>;
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub(crate) enum RockType {
@@ -16,52 +31,26 @@ pub(crate) enum RockType {
     Square,
 }
 
-#[derive(Clone, PartialEq, Eq, Hash)]
-pub(crate) struct Coord {
-    pub(crate) x: usize,
-    pub(crate) y: usize,
-}
-
-impl Coord {
-    fn add(&mut self, coord: (isize, isize)) {
-        self.x = self.x.checked_add_signed(coord.0).unwrap();
-        self.y = self.y.checked_add_signed(coord.1).unwrap();
-    }
-
-    fn get(&self) -> (usize, usize) {
-        (self.x, self.y)
-    }
-}
-
-impl std::fmt::Debug for Coord {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {})", self.x, self.y)
-    }
-}
-
-impl From<(usize, usize)> for Coord {
-    fn from((x, y): (usize, usize)) -> Self {
-        Self { x, y }
-    }
-}
-
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub(crate) struct Rock {
-    pub(crate) positions: Vec<Coord>,
+    pub(crate) positions: Vec<Point>,
     pub(crate) height: usize,
     pub(crate) width: usize,
 }
 
+/// The cells of a rock shape placed with its bottom-left corner at `starting_pos`.
+fn offsets(shape: &[(i64, i64)], starting_pos: Point) -> Vec<Point> {
+    shape
+        .iter()
+        .map(|&offset| starting_pos + Point::from(offset))
+        .collect()
+}
+
 impl Rock {
-    fn new(rock_type: RockType, starting_pos: (usize, usize)) -> Self {
+    fn new(rock_type: RockType, starting_pos: Point) -> Self {
         match rock_type {
             RockType::Horizontal => {
-                let mut positions = Vec::<Coord>::new();
-                for pos in [(0, 0), (1, 0), (2, 0), (3, 0)] {
-                    let mut new_place = Coord::from(starting_pos);
-                    new_place.add(pos);
-                    positions.push(new_place);
-                }
+                let positions = offsets(&[(0, 0), (1, 0), (2, 0), (3, 0)], starting_pos);
                 Self {
                     positions,
                     height: 1,
@@ -69,12 +58,7 @@ impl Rock {
                 }
             }
             RockType::Cross => {
-                let mut positions = Vec::<Coord>::new();
-                for pos in [(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)] {
-                    let mut new_place = Coord::from(starting_pos);
-                    new_place.add(pos);
-                    positions.push(new_place);
-                }
+                let positions = offsets(&[(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)], starting_pos);
                 Self {
                     positions,
                     height: 3,
@@ -82,12 +66,7 @@ impl Rock {
                 }
             }
             RockType::MirrorL => {
-                let mut positions = Vec::<Coord>::new();
-                for pos in [(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)] {
-                    let mut new_place = Coord::from(starting_pos);
-                    new_place.add(pos);
-                    positions.push(new_place);
-                }
+                let positions = offsets(&[(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)], starting_pos);
                 Self {
                     positions,
                     height: 3,
@@ -95,12 +74,7 @@ impl Rock {
                 }
             }
             RockType::Vertical => {
-                let mut positions = Vec::<Coord>::new();
-                for pos in [(0, 0), (0, 1), (0, 2), (0, 3)] {
-                    let mut new_place = Coord::from(starting_pos);
-                    new_place.add(pos);
-                    positions.push(new_place);
-                }
+                let positions = offsets(&[(0, 0), (0, 1), (0, 2), (0, 3)], starting_pos);
                 Self {
                     positions,
                     height: 4,
@@ -108,12 +82,7 @@ impl Rock {
                 }
             }
             RockType::Square => {
-                let mut positions = Vec::<Coord>::new();
-                for pos in [(0, 0), (0, 1), (1, 1), (1, 0)] {
-                    let mut new_place = Coord::from(starting_pos);
-                    new_place.add(pos);
-                    positions.push(new_place);
-                }
+                let positions = offsets(&[(0, 0), (0, 1), (1, 1), (1, 0)], starting_pos);
                 Self {
                     positions,
                     height: 2,
@@ -132,34 +101,34 @@ impl Rock {
         false
     }
 
-    fn check_down_move(&self, incr: (isize, isize), tower: &Tower) -> bool {
+    fn check_down_move(&self, incr: Point, tower: &Tower) -> bool {
         for mut pos in self.positions.clone() {
-            if pos.y == 1 && incr.1 == -1 {
+            if pos.y == 1 && incr.y == -1 {
                 return false;
             }
-            pos.add(incr);
-            if tower.contains(&pos) {
+            pos += incr;
+            if tower[pos] {
                 return false;
             }
         }
         true
     }
 
-    fn check_horizontal_move(&self, incr: (isize, isize), tower: &Tower) -> bool {
+    fn check_horizontal_move(&self, incr: Point, tower: &Tower) -> bool {
         for mut pos in self.positions.clone() {
-            if pos.x == 1 && incr.0 == -1 || pos.x == 7 && incr.0 == 1 {
+            if pos.x == 1 && incr.x == -1 || pos.x == 7 && incr.x == 1 {
                 return false;
             }
-            pos.add(incr);
-            if tower.contains(&pos) {
+            pos += incr;
+            if tower[pos] {
                 return false;
             }
         }
         true
     }
-    fn add(&mut self, incr: (isize, isize)) {
+    fn add(&mut self, incr: Point) {
         for pos in &mut self.positions {
-            pos.add(incr)
+            *pos += incr
         }
     }
 }
@@ -174,49 +143,93 @@ impl std::fmt::Debug for Rock {
     }
 }
 
-fn read_jet_pattern(input: &str) -> Vec<char> {
-    let mut pattern = Vec::<char>::new();
-    for c in input.trim().chars() {
-        pattern.push(c);
+fn read_jet_pattern(input: &str) -> Result<Vec<char>, ParseError> {
+    let jets = input.trim();
+    if jets.is_empty() {
+        return Err(ParseError::new("expected at least one jet", "").at_line(Day17::DAY, 1));
+    }
+    if let Some(column) = jets.find(|c| c != '<' && c != '>') {
+        let rest = &jets[column..];
+        return Err(ParseError::new("not a jet", rest)
+            .within(input, rest)
+            .at_line(Day17::DAY, 1));
     }
-    pattern
+    Ok(jets.chars().collect())
 }
 
-pub fn run() {
-    println!("=== Day 17 ===");
-    println!("Day 17 part 1, solution: {}", solve(INPUT, 2022));
-    // println!(
-    //     "Day 14 part 2, solution: {}",
-    //     solve(INPUT, 1_000_000_000_000)
-    // );
-    println!("=============");
-    println!();
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    /// Number of rocks dropped before the tower is measured.
+    pub rocks: usize,
 }
 
-fn calculate_starting_position(max_rock: usize) -> (usize, usize) {
-    if max_rock == 0 {
-        return (3, 4);
+impl Default for Params {
+    fn default() -> Self {
+        Self { rocks: 2022 }
     }
-    (3, max_rock + 4)
+}
+
+/// Most rocks a run may drop. Every rock is simulated, so the puzzle's part 2 count of a trillion
+/// would never finish.
+const MAX_ROCKS: usize = 1_000_000;
+
+impl Validate for Params {
+    fn validate(&self) -> color_eyre::Result<()> {
+        if self.rocks > MAX_ROCKS {
+            color_eyre::eyre::bail!("rocks must be at most {MAX_ROCKS}, got {}", self.rocks);
+        }
+        Ok(())
+    }
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+
+    type Input = Vec<char>;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        read_jet_pattern(input)
+    }
+
+    fn part1(input: &Self::Input, params: &Params) -> Answer {
+        solve(input, params.rocks).into()
+    }
+}
+
+impl Generate for Day17 {
+    const DEFAULT_SIZE: usize = 10091;
+
+    /// A jet pattern of `size` jets, at least one.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        (0..size.max(1)).map(|_| *rng.choose(&['<', '>'])).collect()
+    }
+}
+
+fn calculate_starting_position(max_rock: i64) -> Point {
+    Point::new(3, max_rock + 4)
 }
 
 fn simulate_rock_fall(
     rock: &mut Rock,
     tower: &Tower,
-    jet_pattern: &Vec<char>,
+    jet_pattern: &[char],
     jet_counter: &mut usize,
 ) {
     loop {
         let index = *jet_counter % jet_pattern.len();
         match jet_pattern[index] {
             '<' => {
-                if rock.check_horizontal_move((-1, 0), tower) {
-                    rock.add((-1, 0));
+                if rock.check_horizontal_move(LEFT, tower) {
+                    rock.add(LEFT);
                 }
             }
             '>' => {
-                if rock.check_horizontal_move((1, 0), tower) {
-                    rock.add((1, 0));
+                if rock.check_horizontal_move(RIGHT, tower) {
+                    rock.add(RIGHT);
                 }
             }
             _ => panic!(
@@ -225,8 +238,8 @@ fn simulate_rock_fall(
             ),
         }
         *jet_counter += 1;
-        if rock.check_down_move((0, -1), tower) {
-            rock.add((0, -1));
+        if rock.check_down_move(DOWN, tower) {
+            rock.add(DOWN);
         } else {
             return;
         }
@@ -236,8 +249,25 @@ fn simulate_rock_fall(
     }
 }
 
-fn solve(input: &str, num_of_rocks: usize) -> usize {
-    let pattern = read_jet_pattern(input);
+/// `tower` with room above `max_rock` for the next rock to start in, grown to at least twice its
+/// height when it has to grow so that growing stays cheap.
+fn room_above(tower: Tower, max_rock: i64) -> Tower {
+    // A rock starts 4 rows above the top and is at most 4 rows tall.
+    let top = max_rock + 7;
+    if top <= tower.max().y {
+        return tower;
+    }
+    let mut taller = Tower::spanning(
+        tower.min(),
+        Point::new(tower.max().x, cmp::max(top, 2 * tower.max().y)),
+    );
+    for (coord, &settled) in tower.iter() {
+        taller[coord] = settled;
+    }
+    taller
+}
+
+fn solve(pattern: &[char], num_of_rocks: usize) -> usize {
     let rock_types = [
         RockType::Horizontal,
         RockType::Cross,
@@ -246,42 +276,61 @@ fn solve(input: &str, num_of_rocks: usize) -> usize {
         RockType::Square,
     ];
 
-    let mut rock_counter = 1usize;
     let mut jet_counter = 0usize;
-    let mut max_rock = 0usize;
-    let mut rock_pos_cache = VecDeque::<Coord>::new();
-    for r_type in rock_types.iter().cycle() {
+    let mut max_rock = 0i64;
+    let mut tower = Tower::spanning(Point::new(1, 1), Point::new(7, 8));
+    for (index, r_type) in rock_types.iter().cycle().take(num_of_rocks).enumerate() {
+        tower = room_above(tower, max_rock);
         let starting_pos = calculate_starting_position(max_rock);
         let mut rock = Rock::new(r_type.clone(), starting_pos);
-        let tower = HashSet::<_>::from_iter(rock_pos_cache.iter().cloned());
-        simulate_rock_fall(&mut rock, &tower, &pattern, &mut jet_counter);
-        for pos in rock.positions {
+        simulate_rock_fall(&mut rock, &tower, pattern, &mut jet_counter);
+        for &pos in &rock.positions {
             max_rock = cmp::max(max_rock, pos.y);
-            if rock_pos_cache.len() > 100 {
-                rock_pos_cache.pop_front();
-            }
-            rock_pos_cache.push_back(pos);
+            tower[pos] = true;
         }
-        if rock_counter == num_of_rocks {
-            break;
-        }
-        rock_counter += 1;
+        trace::emit(|| Event::RockLanded {
+            rock: index,
+            cells: rock.positions,
+            height: max_rock,
+        });
     }
-    max_rock
+    max_rock as usize
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::params;
     use std::assert_eq;
 
     #[test]
     fn part1_works_with_test_input() {
-        assert_eq!(solve(TEST_INPUT, 2022), 3068);
+        assert_eq!(solve(&read_jet_pattern(TEST_INPUT).unwrap(), 2022), 3068);
     }
 
     #[test]
     fn part1_works_with_puzzle_input() {
-        assert_eq!(solve(INPUT, 2022), 3098);
+        assert_eq!(solve(&read_jet_pattern(INPUT).unwrap(), 2022), 3098);
+    }
+
+    #[test]
+    fn read_jet_pattern_reports_invalid_jets() {
+        let err = read_jet_pattern("<<>^>\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (1, 4, "^>"));
+        let err = read_jet_pattern("\n").unwrap_err();
+        assert_eq!(
+            (err.line, err.reason.as_str()),
+            (1, "expected at least one jet")
+        );
+    }
+
+    #[test]
+    fn rocks_are_bounded() {
+        let overrides = toml::toml! { rocks = 1_000_000_000_000_i64 };
+        let err = params::resolve::<Params>(&overrides).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid parameters: rocks must be at most 1000000, got 1000000000000"
+        );
     }
 }
@@ -0,0 +1,214 @@
+use std::collections::HashSet;
+
+use crate::generate::{Generate, Rng};
+use crate::geometry::Point3;
+use crate::params::NoParams;
+use crate::parse::{parse_lines, parse_num, ParseError};
+use crate::search;
+use crate::solution::{Answer, Solution};
+
+pub(crate) const INPUT: &str = include_str!("./input.txt");
+#[allow(dead_code)]
+const TEST_INPUT: &str = include_str!("./test_input.txt");
+
+/// A lava droplet, as the set of 1x1x1 cubes it is made of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Droplet {
+    pub cubes: HashSet<Point3>,
+}
+
+impl Droplet {
+    /// The low and high corners of the smallest box holding every cube, `None` when empty.
+    pub fn bounds(&self) -> Option<(Point3, Point3)> {
+        let mut cubes = self.cubes.iter().copied();
+        let first = cubes.next()?;
+        Some(cubes.fold((first, first), |(min, max), cube| {
+            (min.min_each(cube), max.max_each(cube))
+        }))
+    }
+
+    /// Number of cube faces not touching another cube, those of air pockets included.
+    pub fn surface_area(&self) -> usize {
+        self.cubes
+            .iter()
+            .flat_map(|cube| cube.neighbours6())
+            .filter(|side| !self.cubes.contains(side))
+            .count()
+    }
+
+    /// The air outside the droplet: every position of its bounding box, grown by one on each
+    /// side, that can be reached from a corner of that box without passing through a cube.
+    pub fn outside(&self) -> HashSet<Point3> {
+        let Some((min, max)) = self.bounds() else {
+            return HashSet::new();
+        };
+        let (min, max) = (min - Point3::new(1, 1, 1), max + Point3::new(1, 1, 1));
+        search::distances(min, |air: &Point3| {
+            air.neighbours6()
+                .filter(|next| next.is_within(min, max) && !self.cubes.contains(next))
+                .collect::<Vec<_>>()
+        })
+        .into_keys()
+        .collect()
+    }
+
+    /// Number of cube faces touching the air outside the droplet.
+    pub fn exterior_surface_area(&self) -> usize {
+        let outside = self.outside();
+        self.cubes
+            .iter()
+            .flat_map(|cube| cube.neighbours6())
+            .filter(|side| outside.contains(side))
+            .count()
+    }
+}
+
+fn read_cube(line: &str) -> Result<Point3, ParseError> {
+    let coordinates: Vec<&str> = line.split(',').collect();
+    let [x, y, z] = coordinates[..] else {
+        return Err(ParseError::new("expected <x>,<y>,<z>", line));
+    };
+    let parse = |c: &str, what: &str| parse_num::<i64>(c, what).map_err(|e| e.within(line, c));
+    Ok(Point3::new(parse(x, "x")?, parse(y, "y")?, parse(z, "z")?))
+}
+
+fn read_droplet(input: &str) -> Result<Droplet, ParseError> {
+    let cubes = parse_lines(Day18::DAY, input.lines(), read_cube)?;
+    Ok(Droplet {
+        cubes: cubes.into_iter().collect(),
+    })
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    const DAY: u8 = 18;
+
+    type Input = Droplet;
+    type Params = NoParams;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        read_droplet(input)
+    }
+
+    fn part1(input: &Self::Input, _params: &Self::Params) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input, _params: &Self::Params) -> Answer {
+        part2(input).into()
+    }
+}
+
+impl Generate for Day18 {
+    const DEFAULT_SIZE: usize = 2800;
+
+    /// A blob of `size` cubes grown one face at a time inside a box about the size of the
+    /// puzzle's, leaving some air pockets inside it.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let side = ((size as f64).cbrt() * 1.5).ceil().max(3.0) as i64;
+        let max = Point3::new(side - 1, side - 1, side - 1);
+        let size = size.min((side * side * side) as usize);
+        let seed = Point3::new(side / 2, side / 2, side / 2);
+        let mut cubes = vec![seed];
+        let mut seen = HashSet::from([seed]);
+        while cubes.len() < size {
+            let from = *rng.choose(&cubes);
+            let next = from.neighbours6().nth(rng.usize(0..=5)).unwrap();
+            if next.is_within(Point3::ORIGIN, max) && seen.insert(next) {
+                cubes.push(next);
+            }
+        }
+        rng.shuffle(&mut cubes);
+        let lines: Vec<String> = cubes
+            .iter()
+            .map(|cube| format!("{},{},{}", cube.x, cube.y, cube.z))
+            .collect();
+        lines.join("\n")
+    }
+}
+
+fn part1(droplet: &Droplet) -> usize {
+    droplet.surface_area()
+}
+
+fn part2(droplet: &Droplet) -> usize {
+    droplet.exterior_surface_area()
+}
+
+/// Form of the exterior area checking each exposed face on its own, by walking out from it until
+/// it leaves the bounding box or runs out of air, slow but hard to get wrong.
+#[cfg(test)]
+mod reference {
+    use super::*;
+
+    pub(super) fn part2(droplet: &Droplet) -> usize {
+        let Some((min, max)) = droplet.bounds() else {
+            return 0;
+        };
+        droplet
+            .cubes
+            .iter()
+            .flat_map(|cube| cube.neighbours6())
+            .filter(|side| !droplet.cubes.contains(side))
+            .filter(|&side| {
+                search::shortest_path(
+                    [side],
+                    |air: &Point3| {
+                        air.neighbours6()
+                            .filter(|next| !droplet.cubes.contains(next))
+                            .collect::<Vec<_>>()
+                    },
+                    |air| !air.is_within(min, max),
+                )
+                .is_some()
+            })
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::generate::assert_agree;
+
+    #[test]
+    fn part1_works_with_test_input() {
+        assert_eq!(part1(&read_droplet(TEST_INPUT).unwrap()), 64);
+        assert_eq!(part1(&read_droplet("1,1,1\n2,1,1").unwrap()), 10);
+    }
+
+    #[test]
+    fn part2_works_with_test_input() {
+        assert_eq!(part2(&read_droplet(TEST_INPUT).unwrap()), 58);
+    }
+
+    #[test]
+    fn outside_air_surrounds_the_droplet_but_skips_pockets() {
+        let droplet = read_droplet(TEST_INPUT).unwrap();
+        assert_eq!(
+            droplet.bounds(),
+            Some((Point3::new(1, 1, 1), Point3::new(3, 3, 6)))
+        );
+        let outside = droplet.outside();
+        // The padded box is 5x5x8, less the 13 cubes and the one pocket at 2,2,5.
+        assert_eq!(outside.len(), 5 * 5 * 8 - 13 - 1);
+        assert!(!outside.contains(&Point3::new(2, 2, 5)));
+        assert!(outside.contains(&Point3::new(0, 0, 0)));
+    }
+
+    #[test]
+    fn solvers_agree_with_the_reference() {
+        assert_agree::<Day18, _>(8, 300, part2, reference::part2);
+    }
+
+    #[test]
+    fn read_droplet_reports_invalid_cubes() {
+        let err = read_droplet("1,2,3\n4,-5,x").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 6, "x"));
+        let err = read_droplet("1,2,3\n4,5").unwrap_err();
+        assert_eq!((err.line, err.reason.as_str()), (2, "expected <x>,<y>,<z>"));
+    }
+}
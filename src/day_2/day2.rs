@@ -1,25 +1,30 @@
 use std::str::FromStr;
 
-const INPUT: &str = include_str!("./input.txt");
+use crate::generate::{Generate, Rng};
+use crate::params::NoParams;
+use crate::parse::{parse_lines, ParseError};
+use crate::solution::{Answer, Solution};
+
+pub(crate) const INPUT: &str = include_str!("./input.txt");
 #[allow(dead_code)]
 const TEST_INPUT: &str = include_str!("./test_input.txt");
 
 #[derive(Debug, Clone, Copy)]
-enum Move {
+pub enum Move {
     Rock,
     Paper,
     Scissors,
 }
 
 impl TryFrom<char> for Move {
-    type Error = color_eyre::Report;
+    type Error = ParseError;
 
     fn try_from(c: char) -> Result<Self, Self::Error> {
         match c {
-            'A' => Ok(Move::Rock),
-            'B' => Ok(Move::Paper),
-            'C' => Ok(Move::Scissors),
-            _ => Err(color_eyre::eyre::eyre!("not a valid move: {c:?}")),
+            'A' | 'X' => Ok(Move::Rock),
+            'B' | 'Y' => Ok(Move::Paper),
+            'C' | 'Z' => Ok(Move::Scissors),
+            _ => Err(ParseError::new("not a valid move", c)),
         }
     }
 }
@@ -76,22 +81,24 @@ impl Move {
 }
 
 #[derive(Debug, Clone, Copy)]
-struct Round {
-    theirs: Move,
-    ours: Move,
+pub struct Round {
+    pub theirs: Move,
+    pub ours: Move,
 }
 
 impl FromStr for Round {
-    type Err = color_eyre::Report;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut chars = s.chars();
-        let (Some(theirs), Some(' '), Some(outcome), None) = (chars.next(), chars.next(), chars.next(), chars.next()) else {
-            return Err(color_eyre::eyre::eyre!("expected <theirs>SP<ours>EOF, got {s:?}"));
+        let (Some(theirs), Some(' '), Some(outcome), None) =
+            (chars.next(), chars.next(), chars.next(), chars.next())
+        else {
+            return Err(ParseError::new("expected <theirs>SP<ours>EOF", s));
         };
 
         let theirs = Move::try_from(theirs)?;
-        let outcome = Outcome::try_from(outcome)?;
+        let outcome = Outcome::try_from(outcome).map_err(|e| e.at_column(3))?;
         let ours = outcome.matching_move(theirs);
 
         Ok(Self { theirs, ours })
@@ -99,17 +106,32 @@ impl FromStr for Round {
 }
 
 impl Round {
-    fn outcome(self) -> Outcome {
+    /// Reads the second column as our own move instead of the desired outcome.
+    fn from_moves(s: &str) -> Result<Self, ParseError> {
+        let mut chars = s.chars();
+        let (Some(theirs), Some(' '), Some(ours), None) =
+            (chars.next(), chars.next(), chars.next(), chars.next())
+        else {
+            return Err(ParseError::new("expected <theirs>SP<ours>EOF", s));
+        };
+
+        Ok(Self {
+            theirs: Move::try_from(theirs)?,
+            ours: Move::try_from(ours).map_err(|e| e.at_column(3))?,
+        })
+    }
+
+    pub fn outcome(self) -> Outcome {
         self.ours.outcome(self.theirs)
     }
 
-    fn our_score(self) -> usize {
+    pub fn our_score(self) -> usize {
         self.ours.inherent_points() + self.outcome().inherent_points()
     }
 }
 
 #[derive(Debug, Clone, Copy)]
-enum Outcome {
+pub enum Outcome {
     Win,
     Draw,
     Loss,
@@ -133,43 +155,77 @@ impl Outcome {
 }
 
 impl TryFrom<char> for Outcome {
-    type Error = color_eyre::Report;
+    type Error = ParseError;
 
     fn try_from(c: char) -> Result<Self, Self::Error> {
         match c {
             'X' => Ok(Outcome::Loss),
             'Y' => Ok(Outcome::Draw),
             'Z' => Ok(Outcome::Win),
-            _ => Err(color_eyre::eyre::eyre!("not a valid outcome: {c:?}")),
+            _ => Err(ParseError::new("not a valid outcome", c)),
         }
     }
 }
 
-pub fn run() {
-    color_eyre::install().expect("Unable to setup color eyre");
-    println!("=== Day 1 ===");
-    part1(INPUT);
-    part2(INPUT);
-    println!("=============");
-    println!();
+#[derive(Debug)]
+pub struct StrategyGuide {
+    /// Rounds read as two moves, for part 1.
+    pub moves: Vec<Round>,
+    /// Rounds read as their move and the outcome to reach, for part 2.
+    pub outcomes: Vec<Round>,
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+
+    type Input = StrategyGuide;
+    type Params = NoParams;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        read_input(input)
+    }
+
+    fn part1(input: &Self::Input, _params: &Self::Params) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input, _params: &Self::Params) -> Answer {
+        part2(input).into()
+    }
+}
+
+impl Generate for Day2 {
+    const DEFAULT_SIZE: usize = 2500;
+
+    /// `size` rounds.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let rounds: Vec<String> = (0..size)
+            .map(|_| {
+                format!(
+                    "{} {}",
+                    rng.choose(&['A', 'B', 'C']),
+                    rng.choose(&['X', 'Y', 'Z'])
+                )
+            })
+            .collect();
+        rounds.join("\n")
+    }
 }
 
-fn get_rounds(input: &str) -> color_eyre::Result<Vec<Round>> {
-    let rounds = input
-        .lines()
-        .map(Round::from_str)
-        .collect::<Result<_, _>>()?;
-    Ok(rounds)
+fn read_input(input: &str) -> Result<StrategyGuide, ParseError> {
+    let moves = parse_lines(Day2::DAY, input.lines(), Round::from_moves)?;
+    let outcomes = parse_lines(Day2::DAY, input.lines(), Round::from_str)?;
+    Ok(StrategyGuide { moves, outcomes })
 }
 
-fn part1(input: &str) -> usize {
-    let rounds = get_rounds(input).expect("Could not get rounds!");
-    rounds.iter().map(|&r| r.our_score()).sum()
+fn part1(guide: &StrategyGuide) -> usize {
+    guide.moves.iter().map(|&r| r.our_score()).sum()
 }
 
-fn part2(input: &str) -> usize {
-    let rounds = get_rounds(input).expect("Could not get rounds!");
-    rounds.iter().map(|&r| r.our_score()).sum()
+fn part2(guide: &StrategyGuide) -> usize {
+    guide.outcomes.iter().map(|&r| r.our_score()).sum()
 }
 
 #[cfg(test)]
@@ -180,21 +236,29 @@ mod tests {
 
     #[test]
     fn part1_works_with_test_input() {
-        assert_eq!(part1(TEST_INPUT), 15);
+        assert_eq!(part1(&read_input(TEST_INPUT).unwrap()), 15);
     }
 
     #[test]
     fn part1_works_with_puzzle_input() {
-        assert_eq!(part1(INPUT), 15572);
+        assert_eq!(part1(&read_input(INPUT).unwrap()), 15572);
     }
 
     #[test]
     fn part2_works_with_test_input() {
-        assert_eq!(part2(TEST_INPUT), 12);
+        assert_eq!(part2(&read_input(TEST_INPUT).unwrap()), 12);
     }
 
     #[test]
     fn part2_works_with_puzzle_input() {
-        assert_eq!(part2(INPUT), 198041);
+        assert_eq!(part2(&read_input(INPUT).unwrap()), 16098);
+    }
+
+    #[test]
+    fn read_input_reports_invalid_moves() {
+        let err = read_input("A Y\nB W\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 3, "W"));
+        let err = read_input("A Y\nBX\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 1, "BX"));
     }
 }
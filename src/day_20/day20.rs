@@ -1,52 +1,44 @@
+use serde::Deserialize;
+
+use crate::generate::{Generate, Rng};
+use crate::params::Validate;
+use crate::parse::{parse_lines, parse_num, ParseError};
+use crate::solution::{Answer, Solution};
+
 #[allow(dead_code)]
 const TEST_INPUT: &str = include_str!("./test_input.txt");
-const INPUT: &str = include_str!("./input.txt");
+pub(crate) const INPUT: &str = include_str!("./input.txt");
 
 #[derive(Clone, PartialEq, Eq, Hash)]
-pub(crate) struct Sequence {
-    pub(crate) numbers: Vec<isize>,
+pub struct Sequence {
+    pub numbers: Vec<isize>,
 }
 
 impl Sequence {
     fn new(numbers: Vec<isize>) -> Self {
         Self { numbers }
     }
-    fn add(&mut self, num: isize) {
-        let pos = self
-            .numbers
-            .iter()
-            .position(|&v| v == num)
-            .unwrap_or_else(|| panic!("Cannot find number from sequence: {}", num));
-
-        let mut index = pos;
-        let max_index = self.numbers.len() - 1;
-        if num < 0 {
-            for _ in 0..num.abs() {
-                if index == 0 {
-                    index = max_index;
-                    continue;
-                }
-                index -= 1;
-                if index == 0 {
-                    index = max_index;
-                }
-            }
-            self.numbers.remove(pos);
-            self.numbers.insert(index, num);
-        } else {
-            for _ in 0..num {
-                if index == max_index {
-                    index = 0;
-                    continue;
-                }
-                index += 1;
-                if index == max_index {
-                    index = 0;
-                }
-            }
-            self.numbers.remove(pos);
-            self.numbers.insert(index, num);
+
+    /// The numbers after moving each one, in the order of the file, forward or backward around
+    /// the circle by its value. Equal numbers are told apart by their place in the file.
+    fn mixed(&self) -> Vec<isize> {
+        let len = self.numbers.len();
+        if len < 2 {
+            return self.numbers.clone();
+        }
+        // Places in the file of the numbers, in their current order around the circle.
+        let mut order: Vec<usize> = (0..len).collect();
+        for (place, &num) in self.numbers.iter().enumerate() {
+            let pos = order
+                .iter()
+                .position(|&p| p == place)
+                .expect("every place is in the circle");
+            order.remove(pos);
+            // With the number lifted out, the circle has one place fewer to move through.
+            let to = (pos as isize + num).rem_euclid(len as isize - 1) as usize;
+            order.insert(to, place);
         }
+        order.into_iter().map(|place| self.numbers[place]).collect()
     }
 }
 
@@ -60,58 +52,81 @@ impl std::fmt::Debug for Sequence {
     }
 }
 
-fn read_file(input: &str) -> Sequence {
-    let mut numbers = Vec::<isize>::new();
-    for c in input.lines() {
-        numbers.push(
-            c.trim()
-                .parse::<isize>()
-                .expect(format!("Could not parse the given number {}", c).as_str()),
-        )
+fn read_file(input: &str) -> Result<Sequence, ParseError> {
+    let numbers = parse_lines(Day20::DAY, input.lines(), |c| {
+        parse_num::<isize>(c.trim(), "number").map_err(|e| e.within(c, c.trim()))
+    })?;
+    if !numbers.contains(&0) {
+        return Err(
+            ParseError::new("expected a 0 to count the grove coordinates from", "")
+                .at_line(Day20::DAY, 1),
+        );
     }
-    Sequence::new(numbers)
+    Ok(Sequence::new(numbers))
 }
 
-pub fn run() {
-    println!("=== Day 20 ===");
-    println!("Day 20 part 1, solution: {}", part1(INPUT));
-    // println!(
-    //     "Day 20 part 2, solution: {}",
-    //     solve(INPUT, 1_000_000_000_000)
-    // );
-    println!("=============");
-    println!();
+/// Positions after the 0, counted with wrap-around, whose numbers sum to the grove coordinates.
+const OFFSETS: [usize; 3] = [1000, 2000, 3000];
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    pub offsets: Vec<usize>,
 }
 
-fn part1(input: &str) -> isize {
-    let mut sequence = read_file(input);
-    let encrypted_file = sequence.numbers.clone();
-    for num in &encrypted_file {
-        if *num == 0 {
-            continue;
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            offsets: OFFSETS.to_vec(),
         }
-        sequence.add(*num);
     }
-    let zero_pos = sequence
-        .numbers
+}
+
+impl Validate for Params {}
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    const DAY: u8 = 20;
+
+    type Input = Sequence;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        read_file(input)
+    }
+
+    fn part1(input: &Self::Input, params: &Params) -> Answer {
+        part1(input, &params.offsets).into()
+    }
+}
+
+impl Generate for Day20 {
+    const DEFAULT_SIZE: usize = 5000;
+
+    /// `size` numbers, at least one, with plenty of duplicates and exactly one zero.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut numbers: Vec<String> = (1..size.max(1))
+            .map(|_| match rng.range(-10000..=9999) {
+                0 => "10000".to_string(),
+                n => n.to_string(),
+            })
+            .collect();
+        numbers.insert(rng.usize(0..=numbers.len()), "0".to_string());
+        numbers.join("\n")
+    }
+}
+
+fn part1(sequence: &Sequence, offsets: &[usize]) -> isize {
+    let mixed = sequence.mixed();
+    let zero_pos = mixed
         .iter()
         .position(|&v| v == 0)
-        .expect("Cannot find 0 from the sequence");
-
-    let mut index = zero_pos;
-    let mut coordinates = 0isize;
-    let numbers = [1000, 2000, 3000];
-    for n in 1..=3000 {
-        if index == sequence.numbers.len() - 1 {
-            index = 0;
-            continue;
-        }
-        index += 1;
-        if numbers.contains(&n) {
-            coordinates += sequence.numbers[index];
-        }
-    }
-    coordinates
+        .expect("the parser checks that there is a 0");
+    offsets
+        .iter()
+        .map(|offset| mixed[(zero_pos + offset) % mixed.len()])
+        .sum()
 }
 
 #[cfg(test)]
@@ -121,16 +136,31 @@ mod tests {
 
     #[test]
     fn part1_works_with_test_input() {
-        assert_eq!(part1(TEST_INPUT), 3);
+        assert_eq!(part1(&read_file(TEST_INPUT).unwrap(), &OFFSETS), 3);
     }
 
     #[test]
     fn part1_works_with_puzzle_input() {
-        assert_eq!(part1(INPUT), 3);
+        assert_eq!(part1(&read_file(INPUT).unwrap(), &OFFSETS), 4914);
     }
 
-    // #[test]
-    // fn part1_works_with_puzzle_input() {
-    //     assert_eq!(solve(INPUT, 2022), 3098);
-    // }
+    #[test]
+    fn mixing_moves_every_number_once() {
+        let mut mixed = read_file(TEST_INPUT).unwrap().mixed();
+        let zero_pos = mixed.iter().position(|&v| v == 0).unwrap();
+        mixed.rotate_left(zero_pos);
+        assert_eq!(mixed, [0, 3, -2, 1, 2, -3, 4]);
+    }
+
+    #[test]
+    fn read_file_reports_invalid_numbers() {
+        let err = read_file("1\n2\n 3.5").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (3, 2, "3.5"));
+        let err = read_file("1\n2\n-3").unwrap_err();
+        assert_eq!(
+            (err.line, err.reason.as_str()),
+            (1, "expected a 0 to count the grove coordinates from")
+        );
+        assert!(read_file("").is_err());
+    }
 }
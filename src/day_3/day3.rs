@@ -1,12 +1,18 @@
 use std::collections::HashSet;
 
-const INPUT: &str = include_str!("./input.txt");
+use crate::generate::{Generate, Rng};
+use crate::params::NoParams;
+use crate::parse::{parse_lines, ParseError};
+use crate::solution::{Answer, Solution};
+
+pub(crate) const INPUT: &str = include_str!("./input.txt");
 #[allow(dead_code)]
 const TEST_INPUT: &str = include_str!("./test_input.txt");
 
-struct Rucksack {
-    first: String,
-    second: String,
+#[derive(Debug)]
+pub struct Rucksack {
+    pub first: String,
+    pub second: String,
 }
 
 impl Rucksack {
@@ -39,25 +45,66 @@ fn calculate_priority(item: &char) -> Option<u32> {
     None
 }
 
-fn read_input(input: &str) -> Vec<Rucksack> {
-    let mut rucksacks: Vec<Rucksack> = Vec::new();
-    for v in input.split('\n') {
+fn read_input(input: &str) -> Result<Vec<Rucksack>, ParseError> {
+    parse_lines(Day3::DAY, input.lines(), |v| {
+        if let Some(column) = v.find(|c: char| !c.is_ascii_alphabetic()) {
+            return Err(ParseError::new("not an item", &v[column..]).at_column(column + 1));
+        }
         let (first, second) = v.split_at(v.len() / 2);
-        rucksacks.push(Rucksack::new(first, second));
+        Ok(Rucksack::new(first, second))
+    })
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u8 = 3;
+
+    type Input = Vec<Rucksack>;
+    type Params = NoParams;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        read_input(input)
+    }
+
+    fn part1(input: &Self::Input, _params: &Self::Params) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(input: &Self::Input, _params: &Self::Params) -> Answer {
+        part2(input).into()
     }
-    rucksacks
 }
 
-pub fn run() {
-    println!("=== Day 3 ===");
-    part1(INPUT);
-    part2(INPUT);
-    println!("=============");
-    println!();
+impl Generate for Day3 {
+    const DEFAULT_SIZE: usize = 300;
+
+    /// `size` rucksacks, rounded up to whole groups of three.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut items: Vec<char> = ('a'..='z').chain('A'..='Z').collect();
+        let mut rucksacks = Vec::new();
+        for _ in 0..size.div_ceil(3) {
+            rng.shuffle(&mut items);
+            // Each rucksack of the group gets 17 items of its own: one that goes in both
+            // compartments and eight for each. The badge is the only item they all carry.
+            let (badge, rest) = items.split_first().unwrap();
+            for own in rest.chunks(17) {
+                let (shared, own) = own.split_first().unwrap();
+                let (left, right) = own.split_at(8);
+                let len = rng.usize(2..=16);
+                let mut first: Vec<char> = (0..len).map(|_| *rng.choose(left)).collect();
+                let mut second: Vec<char> = (0..len).map(|_| *rng.choose(right)).collect();
+                (first[0], first[1], second[0]) = (*shared, *badge, *shared);
+                rng.shuffle(&mut first);
+                rng.shuffle(&mut second);
+                rucksacks.push(first.into_iter().chain(second).collect::<String>());
+            }
+        }
+        rucksacks.join("\n")
+    }
 }
 
-fn part1(input: &str) -> u32 {
-    let rucksacks = read_input(input);
+fn part1(rucksacks: &[Rucksack]) -> u32 {
     let mut total_priority = 0u32;
     for rucksack in rucksacks.iter() {
         let first_set = HashSet::<char>::from_iter(rucksack.first.chars());
@@ -70,12 +117,10 @@ fn part1(input: &str) -> u32 {
             .iter()
             .sum::<u32>();
     }
-    println!("Day 3 part 1, solution: {}", &total_priority);
     total_priority
 }
 
-fn part2(input: &str) -> u32 {
-    let rucksacks = read_input(input);
+fn part2(rucksacks: &[Rucksack]) -> u32 {
     let mut total_priority = 0u32;
     for rucksack_group in rucksacks.chunks(3) {
         if rucksack_group.len() != 3 {
@@ -103,7 +148,6 @@ fn part2(input: &str) -> u32 {
             .iter()
             .sum::<u32>();
     }
-    println!("Day 3 part 2, solution: {}", &total_priority);
     total_priority
 }
 
@@ -114,21 +158,27 @@ mod tests {
 
     #[test]
     fn part1_works_with_test_input() {
-        assert_eq!(part1(TEST_INPUT), 157);
+        assert_eq!(part1(&read_input(TEST_INPUT).unwrap()), 157);
     }
 
     #[test]
     fn part1_works_with_puzzle_input() {
-        assert_eq!(part1(INPUT), 7597);
+        assert_eq!(part1(&read_input(INPUT).unwrap()), 7597);
     }
 
     #[test]
     fn part2_works_with_test_input() {
-        assert_eq!(part2(TEST_INPUT), 70);
+        assert_eq!(part2(&read_input(TEST_INPUT).unwrap()), 70);
     }
 
     #[test]
     fn part2_works_with_puzzle_input() {
-        assert_eq!(part2(INPUT), 2607);
+        assert_eq!(part2(&read_input(INPUT).unwrap()), 2607);
+    }
+
+    #[test]
+    fn read_input_reports_invalid_items() {
+        let err = read_input("abcD\nab1d").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 3, "1d"));
     }
 }
@@ -1,136 +1,186 @@
-use std::collections::HashSet;
 use std::fmt;
-use std::num::ParseIntError;
-use std::str::FromStr;
 
-const INPUT: &str = include_str!("./input.txt");
+use crate::generate::{Generate, Rng};
+use crate::interval::Interval;
+use crate::params::NoParams;
+use crate::parse::{parse_lines, parse_num, ParseError};
+use crate::solution::{Answer, Solution};
+
+pub(crate) const INPUT: &str = include_str!("./input.txt");
 #[allow(dead_code)]
 const TEST_INPUT: &str = include_str!("./test_input.txt");
 
-struct Pair {
-    start: u32,
-    end: u32,
+fn read_sections(s: &str) -> Result<Interval, ParseError> {
+    let (start, end) = s
+        .split_once('-')
+        .ok_or_else(|| ParseError::new("expected <start>-<end>", s))?;
+
+    let start_asu32 = parse_num::<u32>(start, "section").map_err(|e| e.within(s, start))?;
+    let end_asu32 = parse_num::<u32>(end, "section").map_err(|e| e.within(s, end))?;
+    Interval::new(start_asu32.into(), end_asu32.into())
+        .ok_or_else(|| ParseError::new("sections end before they start", s))
+}
+
+#[derive(Debug)]
+pub struct AssignmentPair {
+    pub first: Interval,
+    pub second: Interval,
+}
+
+impl AssignmentPair {
+    fn new(first: Interval, second: Interval) -> Self {
+        Self { first, second }
+    }
 }
 
-impl fmt::Display for Pair {
+impl fmt::Display for AssignmentPair {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Pair({}, {})", self.start, self.end)
+        write!(f, "AssignmentPair({:?}, {:?})", self.first, self.second)
     }
 }
 
-impl FromStr for Pair {
-    type Err = ParseIntError;
+fn read_input(input: &str) -> Result<Vec<AssignmentPair>, ParseError> {
+    parse_lines(Day4::DAY, input.lines(), |v| {
+        let split = v
+            .split_once(',')
+            .ok_or_else(|| ParseError::new("expected <pair>,<pair>", v))?;
+        let p1 = read_sections(split.0).map_err(|e| e.within(v, split.0))?;
+        let p2 = read_sections(split.1).map_err(|e| e.within(v, split.1))?;
+
+        Ok(AssignmentPair::new(p1, p2))
+    })
+}
+
+pub struct Day4;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (start, end) = s
-            .split_once("-")
-            .expect(format!("Could not split str {} with '-'", s).as_str());
+impl Solution for Day4 {
+    const DAY: u8 = 4;
 
-        let start_asu32 = start.parse::<u32>()?;
-        let end_asu32 = end.parse::<u32>()?;
+    type Input = Vec<AssignmentPair>;
+    type Params = NoParams;
 
-        Ok(Self {
-            start: start_asu32,
-            end: end_asu32,
-        })
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        read_input(input)
     }
-}
 
-struct AssignmentPair {
-    first: Pair,
-    second: Pair,
-}
+    fn part1(input: &Self::Input, _params: &Self::Params) -> Answer {
+        part1(input).into()
+    }
 
-impl AssignmentPair {
-    fn new(first: Pair, second: Pair) -> Self {
-        Self { first, second }
+    fn part2(input: &Self::Input, _params: &Self::Params) -> Answer {
+        part2(input).into()
     }
 }
 
-impl fmt::Display for AssignmentPair {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "AssignmentPair({}, {})", self.first, self.second)
+impl Generate for Day4 {
+    const DEFAULT_SIZE: usize = 1000;
+
+    /// `size` pairs of section ranges within 1 to 99.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut sections = || {
+            let (a, b) = (rng.range(1..=99), rng.range(1..=99));
+            format!("{}-{}", a.min(b), a.max(b))
+        };
+        let pairs: Vec<String> = (0..size)
+            .map(|_| format!("{},{}", sections(), sections()))
+            .collect();
+        pairs.join("\n")
     }
 }
 
-fn read_input(input: &str) -> Vec<AssignmentPair> {
-    let mut pairs: Vec<AssignmentPair> = Vec::new();
-    for v in input.split('\n') {
-        let split = v
-            .split_once(",")
-            .expect(format!("Could not split line {} with ','", v).as_str());
-        let p1 = Pair::from_str(split.0)
-            .expect(format!("Could not create Pair from str: {}", split.0).as_str());
-        let p2 = Pair::from_str(split.1)
-            .expect(format!("Could not create Pair from str: {}", split.1).as_str());
-
-        pairs.push(AssignmentPair::new(p1, p2));
-    }
-    pairs
+fn part1(pairs: &[AssignmentPair]) -> u32 {
+    let fully_contains = pairs
+        .iter()
+        .filter(|pair| pair.first.covers(pair.second) || pair.second.covers(pair.first))
+        .count();
+    fully_contains as u32
 }
 
-pub fn run() {
-    println!("=== Day 4 ===");
-    part1(INPUT);
-    part2(INPUT);
-    println!("=============");
-    println!();
+fn part2(pairs: &[AssignmentPair]) -> u32 {
+    let overlapping = pairs
+        .iter()
+        .filter(|pair| pair.first.overlaps(pair.second))
+        .count();
+    overlapping as u32
 }
 
-fn part1(input: &str) -> u32 {
-    let pairs = read_input(input);
-    let mut fully_contains = 0u32;
-    for a_pair in pairs.iter() {
-        let first_range = (a_pair.first.start..=a_pair.first.end).collect::<Vec<u32>>();
-        let second_range = (a_pair.second.start..=a_pair.second.end).collect::<Vec<u32>>();
-        if first_range.first().unwrap() <= second_range.first().unwrap()
-            && first_range.last().unwrap() >= second_range.last().unwrap()
-            || first_range.first().unwrap() >= second_range.first().unwrap()
-                && first_range.last().unwrap() <= second_range.last().unwrap()
-        {
-            fully_contains += 1;
+/// Set-based forms of the solvers, slow but hard to get wrong.
+#[cfg(test)]
+mod reference {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn sections(interval: Interval) -> HashSet<i64> {
+        (interval.start()..=interval.end()).collect()
+    }
+
+    pub(super) fn part1(pairs: &[AssignmentPair]) -> u32 {
+        let mut fully_contains = 0u32;
+        for a_pair in pairs.iter() {
+            let (first, second) = (sections(a_pair.first), sections(a_pair.second));
+            if first.is_subset(&second) || second.is_subset(&first) {
+                fully_contains += 1;
+            }
         }
+        fully_contains
     }
-    println!("Day 4 part 1, solution: {}", &fully_contains);
-    fully_contains
-}
 
-fn part2(input: &str) -> u32 {
-    let pairs = read_input(input);
-    let mut fully_contains = 0u32;
-    for a_pair in pairs.iter() {
-        let first_set = (a_pair.first.start..=a_pair.first.end).collect::<HashSet<u32>>();
-        let second_set = (a_pair.second.start..=a_pair.second.end).collect::<HashSet<u32>>();
-        if first_set.intersection(&second_set).next().is_some() {
-            fully_contains += 1
+    pub(super) fn part2(pairs: &[AssignmentPair]) -> u32 {
+        let mut overlapping = 0u32;
+        for a_pair in pairs.iter() {
+            let (first, second) = (sections(a_pair.first), sections(a_pair.second));
+            if first.intersection(&second).next().is_some() {
+                overlapping += 1
+            }
         }
+        overlapping
     }
-    println!("Day 4 part 2, solution: {}", &fully_contains);
-    fully_contains
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::assert_agree;
     use std::assert_eq;
 
     #[test]
     fn part1_works_with_test_input() {
-        assert_eq!(part1(TEST_INPUT), 2);
+        assert_eq!(part1(&read_input(TEST_INPUT).unwrap()), 2);
     }
 
     #[test]
     fn part1_works_with_puzzle_input() {
-        assert_eq!(part1(INPUT), 471);
+        assert_eq!(part1(&read_input(INPUT).unwrap()), 471);
     }
 
     #[test]
     fn part2_works_with_test_input() {
-        assert_eq!(part2(TEST_INPUT), 4);
+        assert_eq!(part2(&read_input(TEST_INPUT).unwrap()), 4);
     }
 
     #[test]
     fn part2_works_with_puzzle_input() {
-        assert_eq!(part2(INPUT), 888);
+        assert_eq!(part2(&read_input(INPUT).unwrap()), 888);
+    }
+
+    #[test]
+    fn solvers_agree_with_the_reference() {
+        assert_agree::<Day4, _>(
+            50,
+            100,
+            |pairs| (part1(pairs), part2(pairs)),
+            |pairs| (reference::part1(pairs), reference::part2(pairs)),
+        );
+    }
+
+    #[test]
+    fn read_input_reports_invalid_sections() {
+        let err = read_input("2-4,6-8\n2-3,4-x").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 7, "x"));
+        let err = read_input("2-4;6-8").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 1));
+        let err = read_input("2-4,8-6").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (1, 5, "8-6"));
     }
 }
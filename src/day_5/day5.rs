@@ -1,93 +1,235 @@
 use std::collections::VecDeque;
-use std::iter;
 
 use regex::Regex;
 
-const INPUT: &str = include_str!("./input.txt");
+use crate::generate::{Generate, Rng};
+use crate::params::NoParams;
+use crate::parse::{parse_lines, parse_num, ParseError};
+use crate::solution::{Answer, Solution};
+
+pub(crate) const INPUT: &str = include_str!("./input.txt");
 #[allow(dead_code)]
 const TEST_INPUT: &str = include_str!("./test_input.txt");
 #[allow(dead_code)]
 const TEST_STACK: &str = "N,Z;D,C,M;P";
+#[allow(dead_code)]
 const STACK: &str = "R,H,M,P,Z;B,J,C,P;D,C,L,G,H,N,S;L,R,S,Q,D,M,T,F;M,Z,T,B,Q,P,S,F;G,B,Z,S,F,T;V,R,N;M,C,V,D,T,L,G,P;L,M,F,J,N,Q,W";
 
-fn create_crates_for_test_input(stack_sequence: &'static str) -> Vec<VecDeque<&'static str>> {
-    let mut crates = Vec::<VecDeque<&'static str>>::new();
+pub type Crates = Vec<VecDeque<char>>;
+
+/// Moves `count` crates between two stacks, numbered from 0. A step asking for more crates than
+/// the stack holds moves the ones there are, so a step from an empty stack moves none. Rejecting
+/// such a step would take running the procedure while parsing it.
+#[derive(Debug, Clone, Copy)]
+pub struct Step {
+    pub count: usize,
+    pub from: usize,
+    pub to: usize,
+}
+
+#[derive(Debug)]
+pub struct Procedure {
+    pub crates: Crates,
+    pub steps: Vec<Step>,
+}
+
+#[allow(dead_code)]
+fn create_crates_for_test_input(stack_sequence: &str) -> Crates {
+    let mut crates = Crates::new();
     for c in stack_sequence.split(';') {
-        let mut deque = VecDeque::<&str>::new();
+        let mut deque = VecDeque::<char>::new();
         for s in c.split(',') {
-            deque.push_front(s);
+            deque.push_front(s.chars().next().unwrap());
         }
         crates.push(deque)
     }
     crates
 }
 
-pub fn run() {
-    println!("=== Day 5 ===");
-    let mut crates = create_crates_for_test_input(STACK);
-    part1(INPUT, &mut crates.clone());
-    part2(INPUT, &mut crates);
-    println!("=============");
-    println!();
+/// Whether `line` is the one numbering the stacks, which ends the drawing.
+fn is_stack_numbers(line: &str) -> bool {
+    !line.trim().is_empty() && line.chars().all(|c| c.is_ascii_digit() || c == ' ')
 }
 
-fn part1(input: &str, crates: &mut Vec<VecDeque<&str>>) -> String {
-    let re = Regex::new(r"^move (\d{1,2}) from (\d{1,2}) to (\d{1,2})$")
-        .expect("Unable to create regex");
-    for line in input.split('\n') {
-        if line.contains("move") {
-            for cap in re.captures_iter(line.trim()) {
-                let repeat = &cap[1].parse::<u32>().unwrap();
-                let from = &cap[2].parse::<u32>().unwrap();
-                let to = &cap[3].parse::<u32>().unwrap();
-                for _ in iter::repeat(repeat).take(*repeat as usize) {
-                    if let Some(deque) = crates.get_mut((*from - 1) as usize) {
-                        let item = deque.pop_back().unwrap();
-                        if let Some(_deque) = crates.get_mut((*to - 1) as usize) {
-                            _deque.push_back(item)
-                        }
-                    }
+/// Reads the stacks from the lines of a drawing, the last of them numbering the stacks.
+fn read_crates(drawing: &[&str]) -> Result<Crates, ParseError> {
+    let Some((numbers, rows)) = drawing.split_last() else {
+        return Ok(Crates::new());
+    };
+    let num_of_stacks = numbers.split_whitespace().count();
+    let rows = parse_lines(Day5::DAY, rows.iter().copied(), |line| {
+        line.char_indices()
+            .skip(1)
+            .step_by(4)
+            .enumerate()
+            .filter(|(_, (_, c))| c.is_ascii_alphabetic())
+            .map(|(index, (column, c))| {
+                if index >= num_of_stacks {
+                    return Err(ParseError::new(
+                        format!("crate beyond the last stack, there are {num_of_stacks}"),
+                        &line[column..=column],
+                    )
+                    .at_column(column + 1));
                 }
-            }
+                Ok((index, c))
+            })
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+    let mut crates = vec![VecDeque::<char>::new(); num_of_stacks];
+    for row in rows.iter().rev() {
+        for &(index, c) in row {
+            crates[index].push_back(c);
+        }
+    }
+    Ok(crates)
+}
+
+/// Reads the steps following the drawing of `input`, which must have `stacks` stacks.
+fn read_steps(input: &str, stacks: usize) -> Result<Vec<Step>, ParseError> {
+    let re = Regex::new(r"^move (\d+) from (\d+) to (\d+)$").expect("Unable to create regex");
+    let stack = |line: &str, m: regex::Match| -> Result<usize, ParseError> {
+        let number =
+            parse_num::<usize>(m.as_str(), "stack").map_err(|e| e.within(line, m.as_str()))?;
+        if !(1..=stacks).contains(&number) {
+            return Err(
+                ParseError::new(format!("no such stack, there are {stacks}"), m.as_str())
+                    .within(line, m.as_str()),
+            );
+        }
+        Ok(number - 1)
+    };
+    let mut in_drawing = input.lines().any(is_stack_numbers);
+    let steps = parse_lines(Day5::DAY, input.lines(), |line| {
+        if in_drawing {
+            in_drawing = !is_stack_numbers(line);
+            return Ok(None);
+        }
+        if line.trim().is_empty() {
+            return Ok(None);
+        }
+        let cap = re
+            .captures(line.trim_end())
+            .ok_or_else(|| ParseError::new("expected move <n> from <stack> to <stack>", line))?;
+        Ok(Some(Step {
+            count: parse_num::<usize>(&cap[1], "count").map_err(|e| e.within(line, &cap[1]))?,
+            from: stack(line, cap.get(2).unwrap())?,
+            to: stack(line, cap.get(3).unwrap())?,
+        }))
+    })?;
+    Ok(steps.into_iter().flatten().collect())
+}
+
+fn read_input(input: &str) -> Result<Procedure, ParseError> {
+    let lines: Vec<&str> = input.lines().collect();
+    let drawing = lines
+        .iter()
+        .position(|line| is_stack_numbers(line))
+        .map_or(&lines[..0], |numbers| &lines[..=numbers]);
+    let crates = read_crates(drawing)?;
+    Ok(Procedure {
+        steps: read_steps(input, crates.len())?,
+        crates,
+    })
+}
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u8 = 5;
+
+    type Input = Procedure;
+    type Params = NoParams;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        read_input(input)
+    }
+
+    fn part1(input: &Self::Input, _params: &Self::Params) -> Answer {
+        part1(&input.steps, &mut input.crates.clone()).into()
+    }
+
+    fn part2(input: &Self::Input, _params: &Self::Params) -> Answer {
+        part2(&input.steps, &mut input.crates.clone()).into()
+    }
+}
+
+impl Generate for Day5 {
+    const DEFAULT_SIZE: usize = 500;
+
+    /// Two to nine stacks, some possibly empty, and `size` moves, at least one, that never take
+    /// more crates than a stack holds.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut heights: Vec<usize> = (0..rng.usize(2..=9)).map(|_| rng.usize(0..=8)).collect();
+        if heights.iter().all(|&h| h == 0) {
+            heights[0] = 1;
         }
+        let tallest = *heights.iter().max().unwrap();
+        let mut drawing: Vec<String> = (0..tallest)
+            .rev()
+            .map(|level| {
+                let row: Vec<String> = heights
+                    .iter()
+                    .map(|&h| {
+                        if level < h {
+                            format!("[{}]", (b'A' + rng.below(26) as u8) as char)
+                        } else {
+                            "   ".to_string()
+                        }
+                    })
+                    .collect();
+                row.join(" ")
+            })
+            .collect();
+        let numbers: Vec<String> = (1..=heights.len()).map(|i| format!(" {i} ")).collect();
+        drawing.push(numbers.join(" "));
+
+        let moves: Vec<String> = (0..size.max(1))
+            .map(|_| {
+                let filled: Vec<usize> = (0..heights.len()).filter(|&i| heights[i] > 0).collect();
+                let from = *rng.choose(&filled);
+                let to = (from + rng.usize(1..=heights.len() - 1)) % heights.len();
+                let count = rng.usize(1..=heights[from]);
+                heights[from] -= count;
+                heights[to] += count;
+                format!("move {count} from {} to {}", from + 1, to + 1)
+            })
+            .collect();
+        format!("{}\n\n{}", drawing.join("\n"), moves.join("\n"))
     }
+}
+
+fn top_of_stacks(crates: &mut [VecDeque<char>]) -> String {
     let mut message = String::from("");
     for c in crates {
-        message.push_str(c.pop_back().unwrap())
+        if let Some(top) = c.pop_back() {
+            message.push(top)
+        }
     }
-    println!("Day 5 part 1, solution: {}", &message);
     message
 }
 
-fn part2(input: &str, crates: &mut Vec<VecDeque<&str>>) -> String {
-    let re = Regex::new(r"^move (\d{1,2}) from (\d{1,2}) to (\d{1,2})$")
-        .expect("Unable to create regex");
-    for line in input.split('\n') {
-        if line.contains("move") {
-            for cap in re.captures_iter(line.trim()) {
-                let repeat = &cap[1].parse::<u32>().unwrap();
-                let mut tmp_deque = VecDeque::<&str>::new();
-                let from = &cap[2].parse::<u32>().unwrap();
-                let to = &cap[3].parse::<u32>().unwrap();
-                for _ in iter::repeat(repeat).take(*repeat as usize) {
-                    if let Some(deque) = crates.get_mut((*from - 1) as usize) {
-                        tmp_deque.push_front(deque.pop_back().unwrap());
-                    }
-                }
-                if let Some(_deque) = crates.get_mut((*to - 1) as usize) {
-                    for &c in tmp_deque.iter() {
-                        _deque.push_back(c)
-                    }
-                }
+fn part1(steps: &[Step], crates: &mut [VecDeque<char>]) -> String {
+    for step in steps {
+        for _ in 0..step.count {
+            if let Some(item) = crates[step.from].pop_back() {
+                crates[step.to].push_back(item)
             }
         }
     }
-    let mut message = String::from("");
-    for c in crates {
-        message.push_str(c.pop_back().unwrap())
+    top_of_stacks(crates)
+}
+
+fn part2(steps: &[Step], crates: &mut [VecDeque<char>]) -> String {
+    for step in steps {
+        let mut tmp_deque = VecDeque::<char>::new();
+        for _ in 0..step.count {
+            if let Some(item) = crates[step.from].pop_back() {
+                tmp_deque.push_front(item);
+            }
+        }
+        crates[step.to].extend(tmp_deque);
     }
-    println!("Day 5 part 2, solution: {}", &message);
-    message
+    top_of_stacks(crates)
 }
 
 #[cfg(test)]
@@ -98,24 +240,79 @@ mod tests {
     #[test]
     fn part1_works_with_test_input() {
         let mut crates = create_crates_for_test_input(TEST_STACK);
-        assert_eq!(part1(TEST_INPUT, &mut crates), "CMZ");
+        assert_eq!(
+            part1(&read_steps(TEST_INPUT, crates.len()).unwrap(), &mut crates),
+            "CMZ"
+        );
     }
 
     #[test]
     fn part1_works_with_puzzle_input() {
         let mut crates = create_crates_for_test_input(STACK);
-        assert_eq!(part1(INPUT, &mut crates), "VQZNJMWTR");
+        assert_eq!(
+            part1(&read_steps(INPUT, crates.len()).unwrap(), &mut crates),
+            "VQZNJMWTR"
+        );
     }
 
     #[test]
     fn part2_works_with_test_input() {
         let mut crates = create_crates_for_test_input(TEST_STACK);
-        assert_eq!(part2(TEST_INPUT, &mut crates), "MCD");
+        assert_eq!(
+            part2(&read_steps(TEST_INPUT, crates.len()).unwrap(), &mut crates),
+            "MCD"
+        );
     }
 
     #[test]
     fn part2_works_with_puzzle_input() {
         let mut crates = create_crates_for_test_input(STACK);
-        assert_eq!(part2(INPUT, &mut crates), "NLCDCLVMQ");
+        assert_eq!(
+            part2(&read_steps(INPUT, crates.len()).unwrap(), &mut crates),
+            "NLCDCLVMQ"
+        );
+    }
+
+    #[test]
+    fn crates_are_read_from_the_drawing() {
+        assert_eq!(
+            read_input(TEST_INPUT).unwrap().crates,
+            create_crates_for_test_input(TEST_STACK)
+        );
+        assert_eq!(
+            read_input(INPUT).unwrap().crates,
+            create_crates_for_test_input(STACK)
+        );
+    }
+
+    #[test]
+    fn a_drawing_without_steps_is_read() {
+        let procedure = read_input("[A]    \n[B] [C]\n 1   2 \n").unwrap();
+        assert!(procedure.steps.is_empty());
+        assert_eq!(top_of_stacks(&mut procedure.crates.clone()), "AC");
+        let procedure = read_input("    [C]\n 1   2 \nmove 1 from 2 to 1").unwrap();
+        assert_eq!(procedure.steps.len(), 1);
+        assert_eq!(procedure.crates.len(), 2);
+        let err = read_input("[A] [B] [C]\n 1   2 \n").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (1, 10, "C"));
+    }
+
+    #[test]
+    fn read_input_reports_crates_beyond_the_last_stack() {
+        let err = read_input("[A] [B] [C]\n 1   2 \n\nmove 1 from 1 to 2").unwrap_err();
+        assert_eq!(
+            (err.line, err.column, err.text.as_str(), err.reason.as_str()),
+            (1, 10, "C", "crate beyond the last stack, there are 2")
+        );
+    }
+
+    #[test]
+    fn read_input_reports_invalid_steps() {
+        let drawing = "[A] [B]\n 1   2 \n\n";
+        let err =
+            read_input(&format!("{drawing}move 1 from 1 to 2\nmove 1 from 3 to 1")).unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (5, 13, "3"));
+        let err = read_input(&format!("{drawing}move one from 1 to 2")).unwrap_err();
+        assert_eq!((err.line, err.column), (4, 1));
     }
 }
@@ -1,67 +1,211 @@
-use std::collections::HashSet;
+use serde::Deserialize;
 
-const INPUT: &str = include_str!("./input.txt");
+use crate::generate::{Generate, Rng};
+use crate::params::Validate;
+use crate::parse::ParseError;
+use crate::solution::{Answer, Solution};
 
-fn read_input(input: &str) -> Vec<char> {
-    input.chars().collect::<Vec<char>>()
+pub(crate) const INPUT: &str = include_str!("./input.txt");
+
+fn read_input(input: &str) -> Result<Vec<char>, ParseError> {
+    let datastream = input.trim_end();
+    if let Some(column) = datastream.find(|c: char| !c.is_ascii_lowercase()) {
+        return Err(
+            ParseError::new("not a signal character", &datastream[column..])
+                .at_column(column + 1)
+                .at_line(Day6::DAY, 1),
+        );
+    }
+    Ok(datastream.chars().collect::<Vec<char>>())
+}
+
+/// Number of distinct characters making a marker.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    /// Start-of-packet marker, part 1.
+    pub packet_marker: usize,
+    /// Start-of-message marker, part 2.
+    pub message_marker: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            packet_marker: 4,
+            message_marker: 14,
+        }
+    }
+}
+
+impl Validate for Params {
+    fn validate(&self) -> color_eyre::Result<()> {
+        if self.packet_marker == 0 || self.message_marker == 0 {
+            color_eyre::eyre::bail!("a marker must be at least 1 character long");
+        }
+        Ok(())
+    }
+}
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = 6;
+
+    type Input = Vec<char>;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        read_input(input)
+    }
+
+    fn part1(input: &Self::Input, params: &Params) -> Answer {
+        solve(input, params.packet_marker).into()
+    }
+
+    fn part2(input: &Self::Input, params: &Params) -> Answer {
+        solve(input, params.message_marker).into()
+    }
+}
+
+impl Generate for Day6 {
+    const DEFAULT_SIZE: usize = 4096;
+
+    /// A datastream of `size` characters, at least 14, whose start-of-message marker is its
+    /// last 14 characters.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        // 13 letters cannot hold a message marker, though they hold plenty of packet markers.
+        let noise: Vec<char> = ('a'..='m').collect();
+        let mut stream: String = (0..size.saturating_sub(14))
+            .map(|_| *rng.choose(&noise))
+            .collect();
+        let mut marker: Vec<char> = ('a'..='z').collect();
+        rng.shuffle(&mut marker);
+        stream.extend(&marker[..14]);
+        stream
+    }
 }
 
-pub fn run() {
-    println!("=== Day 6 ===");
-    println!("Day 6 part 1, solution: {}", solve(INPUT, 4));
-    println!("Day 6 part 2, solution: {}", solve(INPUT, 14));
-    println!("=============");
-    println!();
+/// Characters processed up to the end of the first `window_size` distinct characters, 0 when
+/// there are none.
+fn solve(datastream: &[char], window_size: usize) -> usize {
+    // Index just past where each letter was last seen, and the start of the current run of
+    // distinct characters.
+    let mut seen = [0usize; 26];
+    let mut start = 0usize;
+    for (index, &c) in datastream.iter().enumerate() {
+        let letter = (c as u8 - b'a') as usize;
+        start = start.max(seen[letter]);
+        seen[letter] = index + 1;
+        if index + 1 - start == window_size {
+            return index + 1;
+        }
+    }
+    0
 }
 
-fn solve(input: &str, window_size: usize) -> usize {
-    let datastream = read_input(input);
-    let mut marker = 0usize;
-    for (index, stream) in datastream.windows(window_size).enumerate() {
-        let hash: HashSet<char> = stream.iter().copied().collect();
-        if hash.len() == window_size {
-            marker = index + window_size;
-            break;
+/// Window-by-window form of the solver, slow but hard to get wrong.
+#[cfg(test)]
+mod reference {
+    use std::collections::HashSet;
+
+    pub(super) fn solve(datastream: &[char], window_size: usize) -> usize {
+        let mut marker = 0usize;
+        for (index, stream) in datastream.windows(window_size).enumerate() {
+            let hash: HashSet<char> = stream.iter().copied().collect();
+            if hash.len() == window_size {
+                marker = index + window_size;
+                break;
+            }
         }
+        marker
     }
-    marker
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::assert_agree;
+    use crate::params;
     use std::assert_eq;
 
     #[test]
     fn part1_works_with_test_input() {
         let input = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
-        assert_eq!(solve(input, 4), 7);
+        assert_eq!(solve(&read_input(input).unwrap(), 4), 7);
         let input = "bvwbjplbgvbhsrlpgdmjqwftvncz";
-        assert_eq!(solve(input, 4), 5);
+        assert_eq!(solve(&read_input(input).unwrap(), 4), 5);
         let input = "nppdvjthqldpwncqszvftbrmjlhg";
-        assert_eq!(solve(input, 4), 6);
+        assert_eq!(solve(&read_input(input).unwrap(), 4), 6);
         let input = "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg";
-        assert_eq!(solve(input, 4), 10);
+        assert_eq!(solve(&read_input(input).unwrap(), 4), 10);
         let input = "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw";
-        assert_eq!(solve(input, 4), 11);
+        assert_eq!(solve(&read_input(input).unwrap(), 4), 11);
     }
 
     #[test]
     fn part1_works_with_puzzle_input() {
-        assert_eq!(solve(INPUT, 4), 1142);
+        assert_eq!(solve(&read_input(INPUT).unwrap(), 4), 1142);
     }
 
     #[test]
     fn part2_works_with_test_input() {
-        assert_eq!(solve("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 14), 19);
-        assert_eq!(solve("bvwbjplbgvbhsrlpgdmjqwftvncz", 14), 23);
-        assert_eq!(solve("nppdvjthqldpwncqszvftbrmjlhg", 14), 23);
-        assert_eq!(solve("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 14), 29);
-        assert_eq!(solve("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 14), 26);
+        assert_eq!(
+            solve(&read_input("mjqjpqmgbljsphdztnvjfqwrcgsmlb").unwrap(), 14),
+            19
+        );
+        assert_eq!(
+            solve(&read_input("bvwbjplbgvbhsrlpgdmjqwftvncz").unwrap(), 14),
+            23
+        );
+        assert_eq!(
+            solve(&read_input("nppdvjthqldpwncqszvftbrmjlhg").unwrap(), 14),
+            23
+        );
+        assert_eq!(
+            solve(
+                &read_input("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg").unwrap(),
+                14
+            ),
+            29
+        );
+        assert_eq!(
+            solve(&read_input("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw").unwrap(), 14),
+            26
+        );
     }
 
     #[test]
     fn part2_works_with_puzzle_input() {
-        assert_eq!(solve(INPUT, 14), 2803);
+        assert_eq!(solve(&read_input(INPUT).unwrap(), 14), 2803);
+    }
+
+    #[test]
+    fn solver_agrees_with_the_reference() {
+        for window_size in [1, 4, 14] {
+            assert_agree::<Day6, _>(
+                50,
+                200,
+                |stream| solve(stream, window_size),
+                |stream| reference::solve(stream, window_size),
+            );
+        }
+        assert_eq!(solve(&read_input("abcabc").unwrap(), 4), 0);
+    }
+
+    #[test]
+    fn read_input_reports_invalid_characters() {
+        let err = read_input("abc\ndef\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (1, 4, "\ndef"));
+    }
+
+    #[test]
+    fn empty_marker_is_rejected() {
+        let overrides = toml::toml! { packet_marker = 0 };
+        let err = params::resolve::<Params>(&overrides).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid parameters: a marker must be at least 1 character long"
+        );
     }
 }
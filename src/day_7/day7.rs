@@ -0,0 +1,418 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::generate::{Generate, Rng};
+use crate::params::Validate;
+use crate::parse::{parse_lines, parse_num, ParseError};
+use crate::solution::{Answer, Solution};
+
+pub(crate) const INPUT: &str = include_str!("./input.txt");
+#[allow(dead_code)]
+const TEST_INPUT: &str = include_str!("./test_input.txt");
+
+/// Directories at most this large count towards part 1.
+const SMALL_DIR_LIMIT: u64 = 100_000;
+const DISK_SIZE: u64 = 70_000_000;
+/// Free space the update needs.
+const NEEDED_SPACE: u64 = 30_000_000;
+
+/// Index of a directory in its [`FileSystem`].
+pub type DirId = usize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entry {
+    File { name: String, size: u64 },
+    Dir(DirId),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dir {
+    pub name: String,
+    pub parent: Option<DirId>,
+    /// Files and subdirectories, in the order they were first listed.
+    pub entries: Vec<Entry>,
+    /// Total size of the files below the directory, at any depth.
+    pub size: u64,
+}
+
+/// Directory tree rebuilt from a shell transcript. The root is [`FileSystem::ROOT`] and every
+/// directory comes after its parent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSystem {
+    dirs: Vec<Dir>,
+}
+
+impl FileSystem {
+    pub const ROOT: DirId = 0;
+
+    fn new() -> Self {
+        Self {
+            dirs: vec![Dir {
+                name: "/".to_string(),
+                parent: None,
+                entries: Vec::new(),
+                size: 0,
+            }],
+        }
+    }
+
+    pub fn dir(&self, id: DirId) -> &Dir {
+        &self.dirs[id]
+    }
+
+    /// Every directory, the root first.
+    pub fn dirs(&self) -> impl Iterator<Item = &Dir> {
+        self.dirs.iter()
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.dirs[Self::ROOT].size
+    }
+
+    /// The subdirectory `name` of `parent`, added when it was not listed before.
+    fn subdir(&mut self, parent: DirId, name: &str) -> DirId {
+        let existing = self.dirs[parent]
+            .entries
+            .iter()
+            .find_map(|entry| match entry {
+                Entry::Dir(id) if self.dirs[*id].name == name => Some(*id),
+                _ => None,
+            });
+        existing.unwrap_or_else(|| {
+            let id = self.dirs.len();
+            self.dirs.push(Dir {
+                name: name.to_string(),
+                parent: Some(parent),
+                entries: Vec::new(),
+                size: 0,
+            });
+            self.dirs[parent].entries.push(Entry::Dir(id));
+            id
+        })
+    }
+
+    /// Adds a file to `dir` unless a listing of it was seen before.
+    fn add_file(&mut self, dir: DirId, name: &str, size: u64) {
+        let entries = &mut self.dirs[dir].entries;
+        if !entries
+            .iter()
+            .any(|entry| matches!(entry, Entry::File { name: n, .. } if n == name))
+        {
+            entries.push(Entry::File {
+                name: name.to_string(),
+                size,
+            });
+        }
+    }
+
+    /// Fills in the size of every directory, children being done before their parents.
+    fn compute_sizes(&mut self) {
+        for id in (0..self.dirs.len()).rev() {
+            let files: u64 = self.dirs[id]
+                .entries
+                .iter()
+                .map(|entry| match entry {
+                    Entry::File { size, .. } => *size,
+                    Entry::Dir(_) => 0,
+                })
+                .sum();
+            self.dirs[id].size += files;
+            if let Some(parent) = self.dirs[id].parent {
+                self.dirs[parent].size += self.dirs[id].size;
+            }
+        }
+    }
+
+    /// The smallest directory whose deletion leaves `needed` free on a disk of `disk_size`.
+    pub fn smallest_to_free(&self, disk_size: u64, needed: u64) -> Option<&Dir> {
+        let free = disk_size.saturating_sub(self.total_size());
+        let missing = needed.saturating_sub(free);
+        self.dirs
+            .iter()
+            .filter(|dir| dir.size >= missing)
+            .min_by_key(|dir| dir.size)
+    }
+
+    fn write_dir(&self, f: &mut fmt::Formatter<'_>, id: DirId, depth: usize) -> fmt::Result {
+        let dir = &self.dirs[id];
+        let indent = 2 * depth;
+        writeln!(f, "{:indent$}- {} (dir, size={})", "", dir.name, dir.size)?;
+        for entry in &dir.entries {
+            match entry {
+                Entry::File { name, size } => {
+                    writeln!(f, "{:indent$}  - {name} (file, size={size})", "")?
+                }
+                Entry::Dir(child) => self.write_dir(f, *child, depth + 1)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `tree`-style listing, one entry per line, in the order the transcript listed them.
+impl fmt::Display for FileSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_dir(f, Self::ROOT, 0)
+    }
+}
+
+fn read_transcript(input: &str) -> Result<FileSystem, ParseError> {
+    let mut fs = FileSystem::new();
+    let mut cwd = FileSystem::ROOT;
+    parse_lines(Day7::DAY, input.lines(), |line| {
+        if let Some(command) = line.strip_prefix("$ ") {
+            match command.split_once(' ') {
+                Some(("cd", "/")) => cwd = FileSystem::ROOT,
+                Some(("cd", "..")) => {
+                    cwd = fs.dirs[cwd].parent.ok_or_else(|| {
+                        ParseError::new("the root has no parent", command).within(line, command)
+                    })?
+                }
+                Some(("cd", name)) => cwd = fs.subdir(cwd, name),
+                None if command == "ls" => {}
+                _ => {
+                    return Err(
+                        ParseError::new("expected cd <dir> or ls", command).within(line, command)
+                    )
+                }
+            }
+        } else if let Some(name) = line.strip_prefix("dir ") {
+            fs.subdir(cwd, name);
+        } else {
+            let (size, name) = line
+                .split_once(' ')
+                .ok_or_else(|| ParseError::new("expected a command, dir <name> or a file", line))?;
+            let size = parse_num::<u64>(size, "file size").map_err(|e| e.within(line, size))?;
+            fs.add_file(cwd, name, size);
+        }
+        Ok(())
+    })?;
+    fs.compute_sizes();
+    Ok(fs)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    /// Directories at most this large count towards part 1.
+    pub small_dir_limit: u64,
+    pub disk_size: u64,
+    /// Free space part 2 has to make.
+    pub needed_space: u64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            small_dir_limit: SMALL_DIR_LIMIT,
+            disk_size: DISK_SIZE,
+            needed_space: NEEDED_SPACE,
+        }
+    }
+}
+
+impl Validate for Params {
+    /// Deleting every directory frees the whole disk, so part 2 has an answer exactly when the
+    /// space it needs fits on the disk.
+    fn validate(&self) -> color_eyre::Result<()> {
+        if self.needed_space > self.disk_size {
+            color_eyre::eyre::bail!(
+                "needed_space ({}) is larger than disk_size ({})",
+                self.needed_space,
+                self.disk_size
+            );
+        }
+        Ok(())
+    }
+}
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    const DAY: u8 = 7;
+
+    type Input = FileSystem;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        read_transcript(input)
+    }
+
+    fn part1(input: &Self::Input, params: &Params) -> Answer {
+        part1(input, params.small_dir_limit).into()
+    }
+
+    fn part2(input: &Self::Input, params: &Params) -> Answer {
+        part2(input, params.disk_size, params.needed_space).into()
+    }
+}
+
+impl Generate for Day7 {
+    const DEFAULT_SIZE: usize = 300;
+
+    /// A transcript listing `size` files spread over a random tree of directories.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut children: Vec<Vec<usize>> = vec![Vec::new()];
+        let mut files: Vec<Vec<u64>> = vec![Vec::new()];
+        for _ in 0..size {
+            if rng.chance(30) {
+                let (parent, child) = (rng.usize(0..=children.len() - 1), children.len());
+                children[parent].push(child);
+                children.push(Vec::new());
+                files.push(Vec::new());
+            }
+            let dir = rng.usize(0..=files.len() - 1);
+            files[dir].push(rng.range(1_000..=300_000) as u64);
+        }
+
+        let mut lines = vec!["$ cd /".to_string()];
+        let mut pending = vec![Some(0)];
+        // Depth first, `None` marking the way back up to the parent.
+        while let Some(next) = pending.pop() {
+            let Some(dir) = next else {
+                lines.push("$ cd ..".to_string());
+                continue;
+            };
+            if dir != 0 {
+                lines.push(format!("$ cd d{dir}"));
+            }
+            lines.push("$ ls".to_string());
+            let mut listing: Vec<String> = children[dir]
+                .iter()
+                .map(|child| format!("dir d{child}"))
+                .chain(
+                    files[dir]
+                        .iter()
+                        .enumerate()
+                        .map(|(i, size)| format!("{size} f{i}.txt")),
+                )
+                .collect();
+            rng.shuffle(&mut listing);
+            lines.extend(listing);
+            if dir != 0 {
+                pending.push(None);
+            }
+            pending.extend(children[dir].iter().map(|&child| Some(child)));
+        }
+        lines.join("\n")
+    }
+}
+
+fn part1(fs: &FileSystem, limit: u64) -> u64 {
+    fs.dirs()
+        .map(|dir| dir.size)
+        .filter(|&size| size <= limit)
+        .sum()
+}
+
+fn part2(fs: &FileSystem, disk_size: u64, needed: u64) -> u64 {
+    fs.smallest_to_free(disk_size, needed)
+        .expect("needed_space fits on the disk, so deleting / frees enough")
+        .size
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::params;
+
+    #[test]
+    fn part1_works_with_test_input() {
+        assert_eq!(
+            part1(&read_transcript(TEST_INPUT).unwrap(), SMALL_DIR_LIMIT),
+            95437
+        );
+    }
+
+    #[test]
+    fn part2_works_with_test_input() {
+        let fs = read_transcript(TEST_INPUT).unwrap();
+        assert_eq!(part2(&fs, DISK_SIZE, NEEDED_SPACE), 24933642);
+        assert_eq!(fs.total_size(), 48381165);
+    }
+
+    #[test]
+    fn prints_the_tree_with_sizes() {
+        let fs = read_transcript(TEST_INPUT).unwrap();
+        let expected = "\
+- / (dir, size=48381165)
+  - a (dir, size=94853)
+    - e (dir, size=584)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir, size=24933642)
+    - j (file, size=4060174)
+    - d.log (file, size=8033020)
+    - d.ext (file, size=5626152)
+    - k (file, size=7214296)
+";
+        assert_eq!(fs.to_string(), expected);
+    }
+
+    #[test]
+    fn listing_a_directory_twice_counts_its_files_once() {
+        let fs =
+            read_transcript("$ cd /\n$ ls\n10 a\n$ cd x\n$ ls\n5 b\n$ cd /\n$ ls\n10 a").unwrap();
+        assert_eq!(fs.total_size(), 15);
+        assert_eq!(
+            fs.dirs().map(|dir| dir.name.as_str()).collect::<Vec<_>>(),
+            ["/", "x"]
+        );
+    }
+
+    #[test]
+    fn sizes_add_up_on_generated_transcripts() {
+        for seed in 0..5 {
+            let transcript = Day7::generate(&mut Rng::new(seed), 200);
+            let fs = read_transcript(&transcript).unwrap();
+            let listed: u64 = transcript
+                .lines()
+                .filter_map(|line| line.split_once(' ')?.0.parse::<u64>().ok())
+                .sum();
+            assert_eq!(fs.total_size(), listed, "seed {seed}");
+            for dir in fs.dirs() {
+                let entries: u64 = dir
+                    .entries
+                    .iter()
+                    .map(|entry| match entry {
+                        Entry::File { size, .. } => *size,
+                        Entry::Dir(child) => fs.dir(*child).size,
+                    })
+                    .sum();
+                assert_eq!(dir.size, entries, "seed {seed}, {}", dir.name);
+            }
+        }
+    }
+
+    #[test]
+    fn read_transcript_reports_invalid_lines() {
+        let err = read_transcript("$ cd /\n$ cd ..").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 3, "cd .."));
+        let err = read_transcript("$ ls\n12x a.txt").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 1, "12x"));
+        let err = read_transcript("$ ls\n$ rm a.txt").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+    }
+
+    #[test]
+    fn needed_space_larger_than_the_disk_is_rejected() {
+        let overrides = toml::toml! { disk_size = 100 needed_space = 101 };
+        let err = params::resolve::<Params>(&overrides).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid parameters: needed_space (101) is larger than disk_size (100)"
+        );
+        // A full disk that needs all of its space back can only delete /.
+        let overrides = toml::toml! { disk_size = 48381165 needed_space = 48381165 };
+        let params = params::resolve::<Params>(&overrides).unwrap();
+        let fs = read_transcript(TEST_INPUT).unwrap();
+        assert_eq!(part2(&fs, params.disk_size, params.needed_space), 48381165);
+    }
+}
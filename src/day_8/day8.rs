@@ -1,193 +1,206 @@
+use crate::generate::{Generate, Rng};
+use crate::geometry::{Direction, Point};
+use crate::grid::Grid;
+use crate::params::NoParams;
+use crate::parse::ParseError;
+use crate::solution::{Answer, Solution};
+
 #[allow(dead_code)]
 const TEST_INPUT: &str = include_str!("./test_input.txt");
-const INPUT: &str = include_str!("./input.txt");
+pub(crate) const INPUT: &str = include_str!("./input.txt");
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub(crate) struct GridCoord {
-    pub(crate) x: usize,
-    pub(crate) y: usize,
+fn create_grid_from_input(input: &str) -> Result<Grid<usize>, ParseError> {
+    Grid::parse(Day8::DAY, input, "tree height", |c| {
+        c.to_digit(10).map(|height| height as usize)
+    })
 }
 
-impl std::fmt::Debug for GridCoord {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {})", self.x, self.y)
-    }
-}
+pub struct Day8;
 
-impl From<(usize, usize)> for GridCoord {
-    fn from((x, y): (usize, usize)) -> Self {
-        Self { x, y }
-    }
-}
+impl Solution for Day8 {
+    const DAY: u8 = 8;
 
-pub(crate) struct Grid<T> {
-    width: usize,
-    height: usize,
-    data: Vec<T>,
-}
+    type Input = Grid<usize>;
+    type Params = NoParams;
 
-impl<T> Grid<T>
-where
-    T: Default + Clone,
-{
-    pub(crate) fn new(width: usize, height: usize) -> Self {
-        Self {
-            width,
-            height,
-            data: vec![T::default(); width * height],
-        }
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        create_grid_from_input(input)
     }
 
-    fn in_bounds(&self, coord: GridCoord) -> bool {
-        coord.x < self.width && coord.y < self.height
+    fn part1(input: &Self::Input, _params: &Self::Params) -> Answer {
+        part1(input).into()
     }
 
-    pub(crate) fn insert_cell(&mut self, coord: GridCoord, value: T) {
-        if !self.in_bounds(coord) {
-            panic!(
-                "Out of bounds. Cannot insert a value to grid in coordinate {:?}",
-                coord
-            )
-        }
-        self.data[coord.y * self.width + coord.x] = value
+    fn part2(input: &Self::Input, _params: &Self::Params) -> Answer {
+        part2(input).into()
     }
+}
 
-    pub(crate) fn cell(&self, coord: GridCoord) -> Option<&T> {
-        if !self.in_bounds(coord) {
-            return None;
-        }
-        Some(&self.data[coord.y * self.width + coord.x])
-    }
+impl Generate for Day8 {
+    const DEFAULT_SIZE: usize = 99;
 
-    pub(crate) fn width(&self) -> usize {
-        self.width
+    /// A forest of `size` by `size` trees.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let rows: Vec<String> = (0..size)
+            .map(|_| {
+                (0..size)
+                    .map(|_| (b'0' + rng.below(10) as u8) as char)
+                    .collect()
+            })
+            .collect();
+        rows.join("\n")
     }
+}
 
-    pub(crate) fn height(&self) -> usize {
-        self.height
-    }
+/// The cells a walk in `direction` starts from: those with no neighbour behind them.
+fn edge(grid: &Grid<usize>, direction: Direction) -> impl Iterator<Item = Point> + '_ {
+    let back = direction.opposite().offset();
+    grid.coords()
+        .filter(move |&coord| !grid.contains(coord + back))
 }
 
-fn create_grid_from_input(input: &str) -> Grid<usize> {
-    let width = input.lines().next().unwrap().len();
-    let height = input.lines().count();
+/// `start` and every tree from it to the edge of the grid in `direction`.
+fn line(
+    grid: &Grid<usize>,
+    start: Point,
+    direction: Direction,
+) -> impl Iterator<Item = (Point, usize)> + '_ {
+    let rest = grid.ray(start, direction.offset());
+    std::iter::once((start, &grid[start]))
+        .chain(rest)
+        .map(|(coord, &height)| (coord, height))
+}
 
-    let mut grid = Grid::new(width, height);
-    for (y, line) in input.lines().enumerate() {
-        for (x, col) in line.chars().enumerate() {
-            assert!(col.is_ascii_digit());
-            grid.insert_cell((x, y).into(), col as usize - '0' as usize);
+fn part1(grid: &Grid<usize>) -> usize {
+    // A tree is visible when it is taller than every tree before it along some line.
+    let mut visible = Grid::<bool>::new(grid.width(), grid.height());
+    for direction in Direction::ALL {
+        for start in edge(grid, direction) {
+            let mut tallest = None;
+            for (coord, height) in line(grid, start, direction) {
+                if tallest < Some(height) {
+                    visible[coord] = true;
+                    tallest = Some(height);
+                }
+            }
         }
     }
-    grid
+    visible.iter().filter(|(_, &visible)| visible).count()
 }
 
-pub fn run() {
-    println!("=== Day 8 ===");
-    println!("Day 8 part 1, solution: {}", part1(INPUT));
-    println!("Day 8 part 2, solution: {}", part2(INPUT));
-    println!("=============");
-    println!();
+fn part2(grid: &Grid<usize>) -> usize {
+    let mut scores = Grid::<usize>::from_fn(Point::ORIGIN, grid.width(), grid.height(), |_| 1);
+    for direction in Direction::ALL {
+        for start in edge(grid, direction) {
+            // Walking the line in `direction`, each tree looks back the other way. The stack
+            // holds the positions and heights of the trees that can still block a view, the
+            // tallest at the bottom.
+            let mut blockers: Vec<(usize, usize)> = Vec::new();
+            for (position, (coord, height)) in line(grid, start, direction).enumerate() {
+                while blockers
+                    .last()
+                    .is_some_and(|&(_, blocker)| blocker < height)
+                {
+                    blockers.pop();
+                }
+                let seen = blockers.last().map_or(position, |&(at, _)| position - at);
+                scores[coord] *= seen;
+                blockers.push((position, height));
+            }
+        }
+    }
+    scores.iter().map(|(_, &score)| score).max().unwrap_or(0)
 }
 
-fn part1(input: &str) -> usize {
-    let grid = create_grid_from_input(input);
-    let all_coords = (0..grid.height()).into_iter().flat_map(|y| {
-        (0..grid.width())
-            .into_iter()
-            .map(move |x| GridCoord::from((x, y)))
-    });
-    let num_visible_cells = all_coords
-        .filter(|&coord| {
-            let coord_height = grid.cell(coord).unwrap();
-            let deltas: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
-            deltas.iter().any(|&(dx, dy)| {
-                let mut cells_in_ine = (1..).into_iter().map_while(|i| {
-                    let x = if ((dx * i) + coord.x as isize) < 0 {
-                        return None;
-                    } else {
-                        ((dx * i) + coord.x as isize) as usize
-                    };
-                    let y = if ((dy * i) + coord.y as isize) < 0 {
-                        return None;
-                    } else {
-                        ((dy * i) + coord.y as isize) as usize
-                    };
-                    let coord = GridCoord { x, y };
-                    grid.cell(coord)
-                });
-                cells_in_ine.all(|height| height < coord_height)
+/// Forms of the solvers walking every ray from every tree, slow but hard to get wrong.
+#[cfg(test)]
+mod reference {
+    use super::*;
+
+    pub(super) fn part1(grid: &Grid<usize>) -> usize {
+        let num_visible_cells = grid
+            .coords()
+            .filter(|&coord| {
+                let coord_height = grid.cell(coord).unwrap();
+                Direction::ALL.iter().any(|direction| {
+                    grid.ray(coord, direction.offset())
+                        .all(|(_, height)| height < coord_height)
+                })
             })
-        })
-        .count();
-    num_visible_cells
-}
+            .count();
+        num_visible_cells
+    }
 
-fn visible_trees_in_dir(grid: &Grid<usize>, coord: GridCoord, (dx, dy): (isize, isize)) -> usize {
-    let line = (1..).into_iter().map_while(|i| {
-        let x = if ((dx * i) + coord.x as isize) < 0 {
-            return None;
-        } else {
-            ((dx * i) + coord.x as isize) as usize
-        };
-        let y = if ((dy * i) + coord.y as isize) < 0 {
-            return None;
-        } else {
-            ((dy * i) + coord.y as isize) as usize
-        };
-        let coord = GridCoord { x, y };
-        Some(*grid.cell(coord)?)
-    });
-
-    let mut total = 0;
-    let our_height = *grid.cell(coord).unwrap();
-    for height in line {
-        total += 1;
-        if height >= our_height {
-            break;
+    fn visible_trees_in_dir(grid: &Grid<usize>, coord: Point, direction: Direction) -> usize {
+        let line = grid
+            .ray(coord, direction.offset())
+            .map(|(_, &height)| height);
+
+        let mut total = 0;
+        let our_height = *grid.cell(coord).unwrap();
+        for height in line {
+            total += 1;
+            if height >= our_height {
+                break;
+            }
         }
+        total
     }
-    total
-}
 
-fn scenic_score(grid: &Grid<usize>, coord: GridCoord) -> usize {
-    let dirs: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
-    dirs.into_iter()
-        .map(|(dx, dy)| visible_trees_in_dir(grid, coord, (dx, dy)))
-        .product()
-}
+    fn scenic_score(grid: &Grid<usize>, coord: Point) -> usize {
+        Direction::ALL
+            .into_iter()
+            .map(|direction| visible_trees_in_dir(grid, coord, direction))
+            .product()
+    }
 
-fn part2(input: &str) -> usize {
-    let grid = create_grid_from_input(input);
-    let all_coords = (0..grid.height())
-        .into_iter()
-        .flat_map(|y| (0..grid.width()).map(move |x| GridCoord::from((x, y))));
-    all_coords
-        .map(|coord| scenic_score(&grid, coord))
-        .max()
-        .unwrap()
+    pub(super) fn part2(grid: &Grid<usize>) -> usize {
+        grid.coords()
+            .map(|coord| scenic_score(grid, coord))
+            .max()
+            .unwrap()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::generate::assert_agree;
     use std::assert_eq;
 
     #[test]
     fn part1_works_with_test_input() {
-        assert_eq!(part1(TEST_INPUT), 21);
+        assert_eq!(part1(&create_grid_from_input(TEST_INPUT).unwrap()), 21);
     }
     #[test]
     fn part1_works_with_puzzle_input() {
-        assert_eq!(part1(INPUT), 21);
+        assert_eq!(part1(&create_grid_from_input(INPUT).unwrap()), 1708);
     }
     #[test]
     fn part2_works_with_test_input() {
-        assert_eq!(part2(TEST_INPUT), 8);
+        assert_eq!(part2(&create_grid_from_input(TEST_INPUT).unwrap()), 8);
     }
 
     #[test]
     fn part2_works_with_puzzle_input() {
-        assert_eq!(part2(INPUT), 8);
+        assert_eq!(part2(&create_grid_from_input(INPUT).unwrap()), 504000);
+    }
+
+    #[test]
+    fn solvers_agree_with_the_reference() {
+        assert_agree::<Day8, _>(
+            50,
+            20,
+            |grid| (part1(grid), part2(grid)),
+            |grid| (reference::part1(grid), reference::part2(grid)),
+        );
+    }
+
+    #[test]
+    fn create_grid_reports_invalid_rows() {
+        let err = create_grid_from_input("123\n4a6\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 2, "a6"));
+        let err = create_grid_from_input("123\n45\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 1, "45"));
     }
 }
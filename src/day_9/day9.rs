@@ -1,61 +1,39 @@
 use std::cell::RefCell;
 use std::collections::HashSet;
-use std::iter;
-use std::num::ParseIntError;
 use std::str::FromStr;
 
+use serde::Deserialize;
+
+use crate::generate::{Generate, Rng};
+use crate::geometry::{Direction, Point};
+use crate::params::Validate;
+use crate::parse::{parse_lines, parse_num, ParseError};
+use crate::solution::{Answer, Solution};
+
 #[allow(dead_code)]
 const TEST_INPUT: &str = include_str!("./test_input.txt");
 #[allow(dead_code)]
 const TEST_INPUT_2: &str = include_str!("./test_input1_p2.txt");
-const INPUT: &str = include_str!("./input.txt");
-
-#[derive(Clone, PartialEq, Eq, Hash)]
-pub(crate) struct Coord {
-    pub(crate) x: isize,
-    pub(crate) y: isize,
-}
-
-impl std::fmt::Debug for Coord {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {})", self.x, self.y)
-    }
-}
-
-impl From<(isize, isize)> for Coord {
-    fn from((x, y): (isize, isize)) -> Self {
-        Self { x, y }
-    }
-}
+pub(crate) const INPUT: &str = include_str!("./input.txt");
 
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Head {
-    pub(crate) coord: Coord,
+    pub(crate) coord: Point,
 }
 
 impl Head {
     fn new() -> Self {
         Self {
-            coord: Coord::from((0, 0)),
+            coord: Point::ORIGIN,
         }
     }
 
-    fn move_head(&mut self, command: Command, tails: &[RefCell<Tail>]) {
-        for step in iter::repeat(1).take(command.steps) {
-            match command.direction {
-                Direction::Up => self.coord.y += step as isize,
-                Direction::Down => self.coord.y -= step as isize,
-                Direction::Right => self.coord.x += step as isize,
-                Direction::Left => self.coord.x -= step as isize,
-            }
-            let mut new_coord = self.coord.clone();
-            for (index, tail) in tails.iter().enumerate() {
-                if index == 0 {
-                    tail.borrow_mut().move_tail(&new_coord);
-                    continue;
-                }
-                let nn = tails[index - 1].borrow().get_pos();
-                new_coord = tail.borrow_mut().move_tail(&nn);
+    fn move_head(&mut self, command: &Command, tails: &[RefCell<Tail>]) {
+        for _ in 0..command.steps {
+            self.coord += command.direction.offset();
+            let mut leader = self.coord;
+            for tail in tails {
+                leader = tail.borrow_mut().move_tail(leader);
             }
         }
     }
@@ -63,193 +41,155 @@ impl Head {
 
 impl std::fmt::Debug for Head {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {})", self.coord.x, self.coord.y)
+        write!(f, "{:?}", self.coord)
     }
 }
 
 #[derive(Clone, PartialEq, Eq)]
 pub struct Tail {
-    pub(crate) coord: Coord,
-    pub(crate) visited_positions: HashSet<Coord>,
+    pub(crate) coord: Point,
+    pub(crate) visited_positions: HashSet<Point>,
 }
 
 impl std::fmt::Debug for Tail {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Coordinates({}, {})", self.coord.x, self.coord.y)
+        write!(f, "Coordinates{:?}", self.coord)
     }
 }
 
 impl Tail {
     fn new() -> Self {
-        let mut visited_positions = HashSet::new();
-        visited_positions.insert(Coord::from((0, 0)));
         Self {
-            coord: Coord::from((0, 0)),
-            visited_positions,
+            coord: Point::ORIGIN,
+            visited_positions: HashSet::from([Point::ORIGIN]),
         }
     }
 
-    fn rel_move(&mut self, new_pos: (isize, isize)) {
-        self.coord.x += new_pos.0;
-        self.coord.y += new_pos.1;
-    }
-
-    fn get_pos(&self) -> Coord {
-        self.coord.clone()
-    }
-
-    fn move_tail(&mut self, head_coord: &Coord) -> Coord {
-        if self.coord.x == head_coord.x
-            && (self.coord.y < head_coord.y && self.coord.y.abs_diff(head_coord.y) > 1)
-        {
-            self.coord.y += 1;
-            self.visited_positions.insert(self.coord.clone());
-            return self.coord.clone();
+    /// Follows the knot ahead when it is no longer touching, one step in each axis at most.
+    fn move_tail(&mut self, head_coord: Point) -> Point {
+        if self.coord.chebyshev(head_coord) > 1 {
+            self.coord += (head_coord - self.coord).signum();
+            self.visited_positions.insert(self.coord);
         }
-        if self.coord.x == head_coord.x
-            && (self.coord.y > head_coord.y && self.coord.y.abs_diff(head_coord.y) > 1)
-        {
-            self.coord.y -= 1;
-            self.visited_positions.insert(self.coord.clone());
-            return self.coord.clone();
-        }
-        if self.coord.y == head_coord.y
-            && (self.coord.x < head_coord.x && self.coord.x.abs_diff(head_coord.x) > 1)
-        {
-            self.coord.x += 1;
-            self.visited_positions.insert(self.coord.clone());
-            return self.coord.clone();
-        }
-        if self.coord.y == head_coord.y
-            && (self.coord.x > head_coord.x && self.coord.x.abs_diff(head_coord.x) > 1)
-        {
-            self.coord.x -= 1;
-            self.visited_positions.insert(self.coord.clone());
-            return self.coord.clone();
-        }
-        if head_coord.y - self.coord.y > 1 {
-            if self.coord.x < head_coord.x {
-                self.rel_move((1, 1))
-            } else {
-                self.rel_move((-1, 1))
-            }
-            self.visited_positions.insert(self.coord.clone());
-            return self.coord.clone();
-        }
-        if self.coord.y - head_coord.y > 1 {
-            if self.coord.x < head_coord.x {
-                self.rel_move((1, -1))
-            } else {
-                self.rel_move((-1, -1))
-            }
-            self.visited_positions.insert(self.coord.clone());
-            return self.coord.clone();
-        }
-        if head_coord.x - self.coord.x > 1 {
-            if self.coord.y < head_coord.y {
-                self.rel_move((1, 1))
-            } else {
-                self.rel_move((1, -1))
-            }
-            self.visited_positions.insert(self.coord.clone());
-            return self.coord.clone();
-        }
-        if self.coord.x - head_coord.x > 1 {
-            if self.coord.y < head_coord.y {
-                self.rel_move((-1, 1))
-            } else {
-                self.rel_move((-1, -1))
-            }
-            self.visited_positions.insert(self.coord.clone());
-            return self.coord.clone();
-        }
-        self.coord.clone()
+        self.coord
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Hash)]
-pub enum Direction {
-    Right,
-    Up,
-    Left,
-    Down,
-}
-
-impl FromStr for Direction {
-    type Err = ();
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "r" => Ok(Self::Right),
-            "l" => Ok(Self::Left),
-            "u" => Ok(Self::Up),
-            "d" => Ok(Self::Down),
-            _ => panic!("Unrecognized direction {} encountered!", s),
-        }
+fn read_direction(s: &str) -> Result<Direction, ParseError> {
+    match s.to_lowercase().as_str() {
+        "r" => Ok(Direction::Right),
+        "l" => Ok(Direction::Left),
+        "u" => Ok(Direction::Up),
+        "d" => Ok(Direction::Down),
+        _ => Err(ParseError::new("unrecognized direction", s)),
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Command {
-    pub(crate) direction: Direction,
-    pub(crate) steps: usize,
+    pub direction: Direction,
+    pub steps: usize,
 }
 
 impl FromStr for Command {
-    type Err = ParseIntError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (direction, steps) = s
             .split_once(' ')
-            .unwrap_or_else(|| panic!("Could not split str {} ", s));
-        let steps = steps.parse::<u32>()?;
+            .ok_or_else(|| ParseError::new("expected <direction> <steps>", s))?;
+        let steps = parse_num::<u32>(steps, "step count").map_err(|e| e.within(s, steps))?;
 
         Ok(Self {
-            direction: Direction::from_str(direction)
-                .unwrap_or_else(|_| panic!("Could not create direction from {} ", direction)),
+            direction: read_direction(direction)?,
             steps: steps as usize,
         })
     }
 }
 
-fn read_input(input: &str) -> Vec<Command> {
-    let mut commands = Vec::<Command>::new();
-    for line in input.lines() {
-        let command = Command::from_str(line.trim());
-        commands.push(
-            command
-                .unwrap_or_else(|_| panic!("Failure when creating command from a line: {}", line)),
-        )
+fn read_input(input: &str) -> Result<Vec<Command>, ParseError> {
+    parse_lines(Day9::DAY, input.lines(), |line| {
+        Command::from_str(line.trim()).map_err(|e| e.within(line, line.trim()))
+    })
+}
+
+/// Number of knots following the head.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    pub part1_tails: usize,
+    pub part2_tails: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            part1_tails: 1,
+            part2_tails: 9,
+        }
     }
-    commands
 }
 
-pub fn run() {
-    println!("=== Day 9 ===");
-    println!("Day 9 part 1, solution: {}", part1(INPUT));
-    println!("Day 9 part 2, solution: {}", part2(INPUT));
-    println!("=============");
-    println!();
+/// Most knots a part may follow the head with. Each one remembers every position it visits.
+const MAX_TAILS: usize = 100;
+
+impl Validate for Params {
+    fn validate(&self) -> color_eyre::Result<()> {
+        for (name, tails) in [
+            ("part1_tails", self.part1_tails),
+            ("part2_tails", self.part2_tails),
+        ] {
+            if !(1..=MAX_TAILS).contains(&tails) {
+                color_eyre::eyre::bail!("{name} must be within 1..={MAX_TAILS}, got {tails}");
+            }
+        }
+        Ok(())
+    }
 }
 
-fn part1(input: &str) -> usize {
-    let commands = read_input(input);
-    let mut head = Head::new();
-    let tails = vec![RefCell::new(Tail::new())];
-    for command in commands {
-        head.move_head(command, &tails)
+pub struct Day9;
+
+impl Solution for Day9 {
+    const DAY: u8 = 9;
+
+    type Input = Vec<Command>;
+    type Params = Params;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        read_input(input)
     }
 
-    let visited_places_by_tail = tails.last().unwrap().borrow().visited_positions.len();
-    visited_places_by_tail
+    fn part1(input: &Self::Input, params: &Params) -> Answer {
+        solve(input, params.part1_tails).into()
+    }
+
+    fn part2(input: &Self::Input, params: &Params) -> Answer {
+        solve(input, params.part2_tails).into()
+    }
 }
 
-fn part2(input: &str) -> usize {
-    let commands = read_input(input);
-    let mut head = Head::new();
-    let mut tails = Vec::<RefCell<Tail>>::new();
-    for _ in 0..9 {
-        tails.push(RefCell::new(Tail::new()));
+impl Generate for Day9 {
+    const DEFAULT_SIZE: usize = 2000;
+
+    /// `size` head moves of up to 20 steps.
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let commands: Vec<String> = (0..size)
+            .map(|_| {
+                format!(
+                    "{} {}",
+                    rng.choose(&['R', 'L', 'U', 'D']),
+                    rng.usize(1..=20)
+                )
+            })
+            .collect();
+        commands.join("\n")
     }
+}
+
+/// Positions visited by the last of `tails` knots following the head, at least one.
+fn solve(commands: &[Command], tails: usize) -> usize {
+    let mut head = Head::new();
+    let tails: Vec<RefCell<Tail>> = (0..tails).map(|_| RefCell::new(Tail::new())).collect();
     for command in commands {
         head.move_head(command, &tails)
     }
@@ -260,29 +200,48 @@ fn part2(input: &str) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::params;
     use std::assert_eq;
 
     #[test]
     fn part1_works_with_test_input() {
-        assert_eq!(part1(TEST_INPUT), 13);
+        assert_eq!(solve(&read_input(TEST_INPUT).unwrap(), 1), 13);
     }
 
     #[test]
     fn part1_works_with_puzzle_input() {
-        assert_eq!(part1(INPUT), 6175);
+        assert_eq!(solve(&read_input(INPUT).unwrap(), 1), 6175);
     }
     #[test]
     fn part2_works_with_test_input() {
-        assert_eq!(part2(TEST_INPUT), 1);
+        assert_eq!(solve(&read_input(TEST_INPUT).unwrap(), 9), 1);
     }
 
     #[test]
     fn part2_works_with_test_input_2() {
-        assert_eq!(part2(TEST_INPUT_2), 36);
+        assert_eq!(solve(&read_input(TEST_INPUT_2).unwrap(), 9), 36);
     }
 
     #[test]
     fn part2_works_with_puzzle_input() {
-        assert_eq!(part2(INPUT), 2578);
+        assert_eq!(solve(&read_input(INPUT).unwrap(), 9), 2578);
+    }
+
+    #[test]
+    fn read_input_reports_invalid_commands() {
+        let err = read_input("R 4\nX 2\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 1, "X"));
+        let err = read_input("R 4\nU -2\n").unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 3, "-2"));
+    }
+
+    #[test]
+    fn tails_must_be_at_least_one() {
+        let overrides = toml::toml! { part1_tails = 0 };
+        let err = params::resolve::<Params>(&overrides).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid parameters: part1_tails must be within 1..=100, got 0"
+        );
     }
 }
@@ -0,0 +1,92 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{Result, WrapErr};
+
+use crate::config::Config;
+use crate::http::Client;
+use crate::input;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fetched {
+    /// The input was already in the inputs directory, nothing was requested.
+    Cached(PathBuf),
+    Downloaded(PathBuf),
+}
+
+/// Makes sure the input of `day` is in `inputs_dir`, downloading it only if it is not there yet.
+pub fn fetch(day: u8, config: &Config, inputs_dir: &Path) -> Result<Fetched> {
+    let path = inputs_dir.join(input::file_name(day));
+    if path.exists() {
+        return Ok(Fetched::Cached(path));
+    }
+    let client = Client::new(config, inputs_dir)?;
+    let input = client.get(&format!("/2022/day/{day}/input"))?;
+
+    // Written aside and renamed so that an interrupted download never looks cached.
+    let partial = path.with_extension("part");
+    fs::write(&partial, input)
+        .and_then(|()| fs::rename(&partial, &path))
+        .wrap_err_with(|| format!("could not write {}", path.display()))?;
+    Ok(Fetched::Downloaded(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::http::stub::Server;
+
+    fn config(server: &Server) -> Config {
+        Config {
+            session: Some("abc".to_string()),
+            base_url: server.base_url.clone(),
+            min_request_interval: 0,
+            ..Config::default()
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc-2022-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn input_is_downloaded_once_and_then_cached() {
+        let server = Server::start(|request| (200, format!("input of {}\n", request.path)));
+        let dir = temp_dir("fetch-cache");
+        let path = dir.join("day07.txt");
+
+        assert_eq!(
+            fetch(7, &config(&server), &dir).unwrap(),
+            Fetched::Downloaded(path.clone())
+        );
+        assert_eq!(
+            fetch(7, &config(&server), &dir).unwrap(),
+            Fetched::Cached(path.clone())
+        );
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "input of /2022/day/7/input\n"
+        );
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].cookie.as_deref(), Some("session=abc"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn failed_download_is_not_cached() {
+        let server = Server::start(|_| (404, "not found".to_string()));
+        let dir = temp_dir("fetch-failure");
+
+        let err = fetch(3, &config(&server), &dir).unwrap_err();
+        assert!(err.to_string().contains("404"), "{err}");
+        assert!(!dir.join("day03.txt").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
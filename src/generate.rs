@@ -0,0 +1,148 @@
+//! Random puzzle inputs for stress tests and performance runs.
+//!
+//! Every day implements [`Generate`], producing a valid input of a requested size from an
+//! [`Rng`]. The same seed always gives the same input, on any platform and build.
+
+use std::ops::RangeInclusive;
+
+use crate::solution::Solution;
+
+/// SplitMix64: tiny, fast and fully determined by its seed.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`, `n` being positive.
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "empty range");
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+
+    /// Uniform in `range`, which must not be empty.
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let (start, end) = range.into_inner();
+        let span = end.abs_diff(start).checked_add(1);
+        match span {
+            Some(span) => start.wrapping_add(self.below(span) as i64),
+            None => self.next_u64() as i64,
+        }
+    }
+
+    /// Uniform in `range`, which must not be empty.
+    pub fn usize(&mut self, range: RangeInclusive<usize>) -> usize {
+        self.range(*range.start() as i64..=*range.end() as i64) as usize
+    }
+
+    /// `true` with probability `percent` in 100.
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i as u64 + 1) as usize);
+        }
+    }
+}
+
+/// A generator of valid puzzle inputs for a day.
+pub trait Generate: Solution {
+    /// Size used when none is asked for, about that of the real puzzle input.
+    const DEFAULT_SIZE: usize;
+
+    /// An input of roughly `size` units, lines or characters depending on the day.
+    fn generate(rng: &mut Rng, size: usize) -> String;
+}
+
+/// Checks that `optimized` and `reference` agree on the inputs of `size` generated for `S` from
+/// seeds `0..cases`, showing the first input they disagree on.
+#[cfg(test)]
+pub(crate) fn assert_agree<S: Generate, T: PartialEq + std::fmt::Debug>(
+    cases: u64,
+    size: usize,
+    optimized: impl Fn(&S::Input) -> T,
+    reference: impl Fn(&S::Input) -> T,
+) {
+    for seed in 0..cases {
+        let input = S::generate(&mut Rng::new(seed), size);
+        let parsed = S::parse(&input).unwrap_or_else(|err| panic!("seed {seed}: {err}"));
+        assert_eq!(
+            optimized(&parsed),
+            reference(&parsed),
+            "day {} seed {seed}, input:\n{input}",
+            S::DAY
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::runner;
+    use crate::solution::Part;
+
+    #[test]
+    fn rng_is_reproducible_and_stays_in_range() {
+        let (mut a, mut b) = (Rng::new(7), Rng::new(7));
+        let draws: Vec<i64> = (0..1000).map(|_| a.range(-3..=3)).collect();
+        assert_eq!(
+            draws,
+            (0..1000).map(|_| b.range(-3..=3)).collect::<Vec<_>>()
+        );
+        assert!(draws.iter().all(|d| (-3..=3).contains(d)));
+        assert!((-3..=3).all(|v| draws.contains(&v)));
+        assert_eq!(a.range(5..=5), 5);
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+
+        let mut items: Vec<u32> = (0..20).collect();
+        a.shuffle(&mut items);
+        items.sort();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn every_day_generates_inputs_it_can_parse() {
+        for day in runner::DAYS {
+            for seed in 0..5 {
+                let input = day.generate(seed, 30);
+                assert_eq!(input, day.generate(seed, 30), "day {}", day.day);
+                // Solving no part only parses the input.
+                if let Err(err) = day.solve(&input, &toml::Table::new(), &[], 1) {
+                    panic!("day {} seed {seed}: {err}\n{input}", day.day);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_day_solves_the_smallest_inputs() {
+        for day in runner::DAYS {
+            for size in [0, 1] {
+                let input = day.generate(0, size);
+                if let Err(err) = day.solve(&input, &toml::Table::new(), &Part::ALL, 1) {
+                    panic!("day {} size {size}: {err}\n{input}", day.day);
+                }
+            }
+        }
+    }
+}
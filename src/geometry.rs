@@ -0,0 +1,362 @@
+//! Points and vectors on the integer plane and in integer space.
+//!
+//! The same types serve as positions and as offsets between them. On the plane `y` grows
+//! downwards, the way puzzle inputs are laid out, so [`Direction::Up`] is `(0, -1)`.
+
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize)]
+pub struct Point3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+/// Component-wise arithmetic and the distances shared by both point types.
+macro_rules! point {
+    ($point:ident { $($c:ident),+ }) => {
+        impl $point {
+            pub const ORIGIN: Self = Self { $($c: 0),+ };
+
+            pub const fn new($($c: i64),+) -> Self {
+                Self { $($c),+ }
+            }
+
+            /// Each component clamped to -1, 0 or 1: one step towards the direction of `self`.
+            pub fn signum(self) -> Self {
+                Self { $($c: self.$c.signum()),+ }
+            }
+
+            /// Number of axis-aligned steps between the two points.
+            pub fn manhattan(self, other: Self) -> u64 {
+                0 $(+ self.$c.abs_diff(other.$c))+
+            }
+
+            /// Number of king moves between the two points.
+            pub fn chebyshev(self, other: Self) -> u64 {
+                0 $(.max(self.$c.abs_diff(other.$c)))+
+            }
+
+            /// The smaller of each component: the low corner of the box spanning both points.
+            pub fn min_each(self, other: Self) -> Self {
+                Self { $($c: self.$c.min(other.$c)),+ }
+            }
+
+            /// The larger of each component: the high corner of the box spanning both points.
+            pub fn max_each(self, other: Self) -> Self {
+                Self { $($c: self.$c.max(other.$c)),+ }
+            }
+
+            /// Whether every component lies between those of `min` and `max`, inclusive.
+            pub fn is_within(self, min: Self, max: Self) -> bool {
+                true $(&& (min.$c..=max.$c).contains(&self.$c))+
+            }
+        }
+
+        impl Add for $point {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                Self { $($c: self.$c + other.$c),+ }
+            }
+        }
+
+        impl Sub for $point {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                Self { $($c: self.$c - other.$c),+ }
+            }
+        }
+
+        impl Mul<i64> for $point {
+            type Output = Self;
+
+            fn mul(self, factor: i64) -> Self {
+                Self { $($c: self.$c * factor),+ }
+            }
+        }
+
+        impl Neg for $point {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self { $($c: -self.$c),+ }
+            }
+        }
+
+        impl AddAssign for $point {
+            fn add_assign(&mut self, other: Self) {
+                *self = *self + other;
+            }
+        }
+
+        impl SubAssign for $point {
+            fn sub_assign(&mut self, other: Self) {
+                *self = *self - other;
+            }
+        }
+    };
+}
+
+point!(Point { x, y });
+point!(Point3 { x, y, z });
+
+impl Point {
+    /// The four points sharing an edge with this one.
+    pub fn neighbours4(self) -> impl Iterator<Item = Point> {
+        Direction::ALL.into_iter().map(move |d| self + d.offset())
+    }
+
+    /// The eight points sharing an edge or a corner with this one.
+    pub fn neighbours8(self) -> impl Iterator<Item = Point> {
+        Direction8::ALL.into_iter().map(move |d| self + d.offset())
+    }
+
+    /// Both coordinates as indices, `None` if either is negative.
+    pub fn to_usize(self) -> Option<(usize, usize)> {
+        Some((usize::try_from(self.x).ok()?, usize::try_from(self.y).ok()?))
+    }
+}
+
+impl Point3 {
+    /// The six points sharing a face with this one.
+    pub fn neighbours6(self) -> impl Iterator<Item = Point3> {
+        const FACES: [Point3; 6] = [
+            Point3::new(1, 0, 0),
+            Point3::new(-1, 0, 0),
+            Point3::new(0, 1, 0),
+            Point3::new(0, -1, 0),
+            Point3::new(0, 0, 1),
+            Point3::new(0, 0, -1),
+        ];
+        FACES.into_iter().map(move |face| self + face)
+    }
+
+    /// The 26 points sharing a face, an edge or a corner with this one.
+    pub fn neighbours26(self) -> impl Iterator<Item = Point3> {
+        (-1..=1)
+            .flat_map(|x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| Point3::new(x, y, z))))
+            .filter(|offset| *offset != Point3::ORIGIN)
+            .map(move |offset| self + offset)
+    }
+}
+
+impl fmt::Debug for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl fmt::Debug for Point3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+impl From<(i64, i64)> for Point {
+    fn from((x, y): (i64, i64)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<(i64, i64, i64)> for Point3 {
+    fn from((x, y, z): (i64, i64, i64)) -> Self {
+        Self { x, y, z }
+    }
+}
+
+/// Fails when a coordinate does not fit in an `i64`.
+impl TryFrom<(usize, usize)> for Point {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from((x, y): (usize, usize)) -> Result<Self, Self::Error> {
+        Ok(Self {
+            x: i64::try_from(x)?,
+            y: i64::try_from(y)?,
+        })
+    }
+}
+
+/// Fails when a coordinate is negative.
+impl TryFrom<Point> for (usize, usize) {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(point: Point) -> Result<Self, Self::Error> {
+        Ok((usize::try_from(point.x)?, usize::try_from(point.y)?))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// Clockwise, starting from [`Direction::Up`].
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    pub fn offset(self) -> Point {
+        match self {
+            Direction::Up => Point::new(0, -1),
+            Direction::Right => Point::new(1, 0),
+            Direction::Down => Point::new(0, 1),
+            Direction::Left => Point::new(-1, 0),
+        }
+    }
+
+    /// The direction of a single step `offset`, if it is one.
+    pub fn from_offset(offset: Point) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.offset() == offset)
+    }
+
+    /// Arrow pointing this way, as drawn on puzzle maps: `^`, `>`, `v` or `<`.
+    pub fn arrow(self) -> char {
+        match self {
+            Direction::Up => '^',
+            Direction::Right => '>',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+}
+
+/// The eight compass directions, `N` being [`Direction::Up`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction8 {
+    N,
+    NE,
+    E,
+    SE,
+    S,
+    SW,
+    W,
+    NW,
+}
+
+impl Direction8 {
+    /// Clockwise, starting from [`Direction8::N`].
+    pub const ALL: [Direction8; 8] = [
+        Direction8::N,
+        Direction8::NE,
+        Direction8::E,
+        Direction8::SE,
+        Direction8::S,
+        Direction8::SW,
+        Direction8::W,
+        Direction8::NW,
+    ];
+
+    pub fn offset(self) -> Point {
+        match self {
+            Direction8::N => Point::new(0, -1),
+            Direction8::NE => Point::new(1, -1),
+            Direction8::E => Point::new(1, 0),
+            Direction8::SE => Point::new(1, 1),
+            Direction8::S => Point::new(0, 1),
+            Direction8::SW => Point::new(-1, 1),
+            Direction8::W => Point::new(-1, 0),
+            Direction8::NW => Point::new(-1, -1),
+        }
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(direction: Direction) -> Self {
+        Direction8::ALL[direction as usize * 2]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn arithmetic_and_distances() {
+        let (a, b) = (Point::new(1, -2), Point::new(-3, 4));
+        assert_eq!(a + b, Point::new(-2, 2));
+        assert_eq!(a - b, Point::new(4, -6));
+        assert_eq!(-a * 3, Point::new(-3, 6));
+        assert_eq!((b - a).signum(), Point::new(-1, 1));
+        assert_eq!((a.manhattan(b), a.chebyshev(b)), (10, 6));
+
+        let c = Point3::new(1, 2, 3);
+        assert_eq!(c.manhattan(Point3::ORIGIN), 6);
+        assert_eq!(c.chebyshev(-c), 6);
+        assert_eq!(c.min_each(Point3::new(2, 0, 3)), Point3::new(1, 0, 3));
+        assert_eq!(c.max_each(Point3::new(2, 0, 3)), Point3::new(2, 2, 3));
+        assert!(c.is_within(Point3::ORIGIN, c));
+        assert!(!a.is_within(Point::ORIGIN, Point::new(2, 2)));
+    }
+
+    #[test]
+    fn neighbours_are_distinct_and_adjacent() {
+        let p = Point::new(5, 5);
+        let n4: HashSet<Point> = p.neighbours4().collect();
+        let n8: HashSet<Point> = p.neighbours8().collect();
+        assert_eq!((n4.len(), n8.len()), (4, 8));
+        assert!(n4.is_subset(&n8));
+        assert!(n4.iter().all(|&n| n.manhattan(p) == 1));
+        assert!(n8.iter().all(|&n| n.chebyshev(p) == 1));
+
+        let q = Point3::new(0, 0, 0);
+        assert_eq!(q.neighbours6().filter(|n| n.manhattan(q) == 1).count(), 6);
+        assert_eq!(q.neighbours26().collect::<HashSet<_>>().len(), 26);
+    }
+
+    #[test]
+    fn directions_turn_and_convert() {
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+        assert_eq!(Direction::Left.opposite(), Direction::Right);
+        assert_eq!(Direction::from_offset(Point::new(1, 1)), None);
+        assert_eq!(Direction::Down.arrow(), 'v');
+        assert_eq!(Direction8::from(Direction::Down), Direction8::S);
+        for d in Direction::ALL {
+            assert_eq!(Direction::from_offset(d.offset()), Some(d));
+            assert_eq!(Direction8::from(d).offset(), d.offset());
+        }
+    }
+
+    #[test]
+    fn checked_conversions() {
+        assert_eq!(Point::new(3, 4).to_usize(), Some((3, 4)));
+        assert_eq!(Point::new(3, -1).to_usize(), None);
+        assert!(<(usize, usize)>::try_from(Point::new(-1, 0)).is_err());
+        assert!(Point::try_from((usize::MAX, 0)).is_err());
+        assert_eq!(Point::try_from((2usize, 7usize)), Ok(Point::new(2, 7)));
+    }
+}
@@ -0,0 +1,304 @@
+//! Dense rectangular grids addressed by [`Point`].
+//!
+//! A grid covers the points from [`Grid::min`] to [`Grid::max`]. Grids parsed from puzzle input
+//! start at the origin, while [`Grid::spanning`] places one anywhere on the plane, negative
+//! coordinates included, for worlds whose extent is only known once the input is read.
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::geometry::{Direction, Point};
+use crate::parse::{parse_lines, ParseError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    origin: Point,
+    width: usize,
+    height: usize,
+    data: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Grid of `width` by `height` cells whose top-left cell is `origin`, each cell set to
+    /// `cell(point)`.
+    pub fn from_fn(
+        origin: Point,
+        width: usize,
+        height: usize,
+        mut cell: impl FnMut(Point) -> T,
+    ) -> Self {
+        let (w, h) = (width as i64, height as i64);
+        let data = (0..h)
+            .flat_map(|y| (0..w).map(move |x| origin + Point::new(x, y)))
+            .map(&mut cell)
+            .collect();
+        Self {
+            origin,
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Parses a character map, one row per line, turning each character into a cell with
+    /// `cell`. Characters it rejects are reported as not being a `what`.
+    pub fn parse(
+        day: u8,
+        input: &str,
+        what: &str,
+        mut cell: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        let width = input.lines().next().map_or(0, |line| line.chars().count());
+        let rows = parse_lines(day, input.lines(), |line| {
+            let row = line
+                .char_indices()
+                .map(|(x, c)| {
+                    cell(c).ok_or_else(|| {
+                        ParseError::new(format!("not a {what}"), &line[x..]).at_column(x + 1)
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if row.len() != width {
+                return Err(ParseError::new(
+                    format!("expected a row of {width} cells, got {}", row.len()),
+                    line,
+                ));
+            }
+            Ok(row)
+        })?;
+
+        Ok(Self {
+            origin: Point::ORIGIN,
+            width,
+            height: rows.len(),
+            data: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The top-left cell.
+    pub fn min(&self) -> Point {
+        self.origin
+    }
+
+    /// The bottom-right cell.
+    pub fn max(&self) -> Point {
+        self.origin + Point::new(self.width as i64 - 1, self.height as i64 - 1)
+    }
+
+    /// Index of `coord` into `data`, `None` outside the grid.
+    fn index(&self, coord: Point) -> Option<usize> {
+        let (x, y) = (coord - self.origin).to_usize()?;
+        (x < self.width && y < self.height).then_some(y * self.width + x)
+    }
+
+    pub fn contains(&self, coord: Point) -> bool {
+        self.index(coord).is_some()
+    }
+
+    pub fn cell(&self, coord: Point) -> Option<&T> {
+        Some(&self.data[self.index(coord)?])
+    }
+
+    pub fn cell_mut(&mut self, coord: Point) -> Option<&mut T> {
+        let index = self.index(coord)?;
+        Some(&mut self.data[index])
+    }
+
+    pub fn insert_cell(&mut self, coord: Point, value: T) {
+        self[coord] = value
+    }
+
+    /// Every coordinate of the grid, row by row.
+    pub fn coords(&self) -> impl Iterator<Item = Point> {
+        let (origin, width, height) = (self.origin, self.width as i64, self.height as i64);
+        (0..height).flat_map(move |y| (0..width).map(move |x| origin + Point::new(x, y)))
+    }
+
+    /// Every cell with its coordinate, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.coords().zip(&self.data)
+    }
+
+    /// The cells met walking from `from` in steps of `step`, `from` itself excluded, until the
+    /// walk leaves the grid. `step` must not be zero.
+    pub fn ray(&self, from: Point, step: Point) -> impl Iterator<Item = (Point, &T)> {
+        (1..)
+            .map(move |i| from + step * i)
+            .map_while(|coord| Some((coord, self.cell(coord)?)))
+    }
+
+    /// The cells of row `y`, left to right. Empty when the row is outside the grid.
+    pub fn row(&self, y: i64) -> impl Iterator<Item = &T> {
+        let start = Point::new(self.origin.x - 1, y);
+        self.ray(start, Direction::Right.offset())
+            .map(|(_, cell)| cell)
+    }
+
+    /// The cells of column `x`, top to bottom. Empty when the column is outside the grid.
+    pub fn column(&self, x: i64) -> impl Iterator<Item = &T> {
+        let start = Point::new(x, self.origin.y - 1);
+        self.ray(start, Direction::Down.offset())
+            .map(|(_, cell)| cell)
+    }
+
+    /// The cells sharing an edge with `coord`.
+    pub fn neighbours4(&self, coord: Point) -> impl Iterator<Item = (Point, &T)> {
+        coord.neighbours4().filter_map(|n| Some((n, self.cell(n)?)))
+    }
+
+    /// The cells sharing an edge or a corner with `coord`.
+    pub fn neighbours8(&self, coord: Point) -> impl Iterator<Item = (Point, &T)> {
+        coord.neighbours8().filter_map(|n| Some((n, self.cell(n)?)))
+    }
+}
+
+impl<T: Default + Clone> Grid<T> {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::from_fn(Point::ORIGIN, width, height, |_| T::default())
+    }
+
+    /// Grid covering every point from `min` to `max`, both included.
+    pub fn spanning(min: Point, max: Point) -> Self {
+        let (width, height) = (max - min + Point::new(1, 1))
+            .to_usize()
+            .expect("max must not be left of or above min");
+        Self::from_fn(min, width, height, |_| T::default())
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// Mirrors the grid along its main diagonal, so rows become columns.
+    pub fn transpose(&self) -> Self {
+        let origin = Point::new(self.origin.y, self.origin.x);
+        Self::from_fn(origin, self.height, self.width, |p| {
+            self[Point::new(p.y, p.x)].clone()
+        })
+    }
+
+    /// Turns the grid a quarter clockwise about its top-left cell.
+    pub fn rotate_right(&self) -> Self {
+        let h = self.height as i64;
+        self.rotated(|p| Point::new(p.y, h - 1 - p.x))
+    }
+
+    /// Turns the grid a quarter anticlockwise about its top-left cell.
+    pub fn rotate_left(&self) -> Self {
+        let w = self.width as i64;
+        self.rotated(|p| Point::new(w - 1 - p.y, p.x))
+    }
+
+    /// Quarter turn, `source` mapping each offset in the result to the offset it came from.
+    fn rotated(&self, source: impl Fn(Point) -> Point) -> Self {
+        Self::from_fn(self.origin, self.height, self.width, |p| {
+            self[self.origin + source(p - self.origin)].clone()
+        })
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, coord: Point) -> &T {
+        self.cell(coord)
+            .unwrap_or_else(|| panic!("{coord:?} is outside the grid"))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, coord: Point) -> &mut T {
+        self.cell_mut(coord)
+            .unwrap_or_else(|| panic!("{coord:?} is outside the grid"))
+    }
+}
+
+/// One line per row, with no separator between cells.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.data.chunks(self.width.max(1)) {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const MAP: &str = "abc\ndef\n";
+
+    fn letters() -> Grid<char> {
+        Grid::parse(0, MAP, "letter", |c| c.is_ascii_lowercase().then_some(c)).unwrap()
+    }
+
+    #[test]
+    fn parses_and_renders_character_maps() {
+        let grid = letters();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Point::new(2, 1)], 'f');
+        assert_eq!(grid.to_string(), MAP);
+
+        let err = Grid::parse(0, "abc\ndE\n", "letter", |c| {
+            c.is_ascii_lowercase().then_some(c)
+        })
+        .unwrap_err();
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 2, "E"));
+        let err = Grid::parse(0, "abc\nde\n", "letter", Some).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+    }
+
+    #[test]
+    fn rows_columns_and_rays() {
+        let grid = letters();
+        assert_eq!(grid.row(1).collect::<String>(), "def");
+        assert_eq!(grid.column(2).collect::<String>(), "cf");
+        assert_eq!(grid.row(2).count(), 0);
+
+        let ray: Vec<_> = grid.ray(Point::new(0, 0), Point::new(1, 1)).collect();
+        assert_eq!(ray, [(Point::new(1, 1), &'e')]);
+        let mut around: Vec<char> = grid
+            .neighbours8(Point::new(0, 0))
+            .map(|(_, &c)| c)
+            .collect();
+        around.sort();
+        assert_eq!(around, ['b', 'd', 'e']);
+        assert_eq!(grid.neighbours4(Point::new(1, 0)).count(), 3);
+    }
+
+    #[test]
+    fn transposes_and_rotates() {
+        let grid = letters();
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(grid.rotate_right().to_string(), "da\neb\nfc\n");
+        assert_eq!(grid.rotate_left().to_string(), "cf\nbe\nad\n");
+        assert_eq!(grid.rotate_right().rotate_left(), grid);
+    }
+
+    #[test]
+    fn spanning_grids_accept_negative_coordinates() {
+        let mut grid = Grid::<u8>::spanning(Point::new(-2, -1), Point::new(2, 1));
+        assert_eq!((grid.width(), grid.height()), (5, 3));
+        assert_eq!(
+            (grid.min(), grid.max()),
+            (Point::new(-2, -1), Point::new(2, 1))
+        );
+        grid[Point::new(-2, -1)] = 7;
+        assert_eq!(grid.iter().next(), Some((Point::new(-2, -1), &7)));
+        assert_eq!(grid.cell(Point::new(3, 0)), None);
+        assert!(grid.contains(Point::ORIGIN));
+        assert_eq!(grid.row(-1).next(), Some(&7));
+    }
+}
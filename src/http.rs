@@ -0,0 +1,222 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use color_eyre::eyre::{bail, eyre, Result, WrapErr};
+
+use crate::config::Config;
+
+const USER_AGENT: &str = concat!(
+    "github.com/mikeleppane/AoC-2022-Rust ",
+    env!("CARGO_PKG_NAME"),
+    "/",
+    env!("CARGO_PKG_VERSION")
+);
+
+/// Keeps requests at least `interval` apart, across runs, by stamping the time of the last one
+/// into a file.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    stamp: PathBuf,
+    interval: Duration,
+}
+
+impl Throttle {
+    pub fn new(stamp: PathBuf, interval: Duration) -> Self {
+        Self { stamp, interval }
+    }
+
+    fn last_request(&self) -> Option<SystemTime> {
+        let millis = fs::read_to_string(&self.stamp).ok()?.trim().parse().ok()?;
+        Some(UNIX_EPOCH + Duration::from_millis(millis))
+    }
+
+    /// Time left until the next request is allowed.
+    pub fn remaining(&self) -> Duration {
+        self.last_request()
+            .and_then(|last| last.elapsed().ok())
+            .map_or(Duration::ZERO, |elapsed| {
+                self.interval.saturating_sub(elapsed)
+            })
+    }
+
+    /// Sleeps until a request is allowed, then records one as made now.
+    pub fn wait(&self) -> Result<()> {
+        thread::sleep(self.remaining());
+        if let Some(dir) = self.stamp.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+        fs::write(&self.stamp, now.as_millis().to_string())
+            .wrap_err_with(|| format!("could not write {}", self.stamp.display()))
+    }
+}
+
+/// Authenticated client for the puzzle server.
+pub struct Client {
+    agent: ureq::Agent,
+    base_url: String,
+    session: String,
+    throttle: Throttle,
+}
+
+impl Client {
+    /// Client for the server in `config`, remembering its last request in `state_dir`.
+    pub fn new(config: &Config, state_dir: &Path) -> Result<Self> {
+        let Some(session) = config.session.clone() else {
+            bail!("no session token, set `session` in the config file");
+        };
+        Ok(Self {
+            agent: ureq::AgentBuilder::new()
+                .user_agent(USER_AGENT)
+                .timeout(Duration::from_secs(30))
+                .build(),
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            session,
+            throttle: Throttle::new(
+                state_dir.join(".last-request"),
+                Duration::from_secs(config.min_request_interval),
+            ),
+        })
+    }
+
+    fn send(
+        &self,
+        method: &str,
+        path: &str,
+        send: impl FnOnce(ureq::Request) -> Result<ureq::Response, Box<ureq::Error>>,
+    ) -> Result<String> {
+        let url = format!("{}{path}", self.base_url);
+        self.throttle.wait()?;
+        let request = self
+            .agent
+            .request(method, &url)
+            .set("Cookie", &format!("session={}", self.session));
+        let response = send(request).map_err(|err| match *err {
+            ureq::Error::Status(status, _) => eyre!("{method} {url} failed with status {status}"),
+            err => eyre!("{method} {url} failed: {err}"),
+        })?;
+        response
+            .into_string()
+            .wrap_err_with(|| format!("could not read response of {url}"))
+    }
+
+    /// GETs `path`, relative to the base URL, and returns the body.
+    pub fn get(&self, path: &str) -> Result<String> {
+        self.send("GET", path, |request| request.call().map_err(Box::new))
+    }
+
+    /// POSTs `form` url-encoded to `path`, relative to the base URL, and returns the body.
+    pub fn post_form(&self, path: &str, form: &[(&str, &str)]) -> Result<String> {
+        self.send("POST", path, |request| {
+            request.send_form(form).map_err(Box::new)
+        })
+    }
+}
+
+/// Minimal HTTP server answering every request through a handler, for exercising the client.
+#[cfg(test)]
+pub mod stub {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Request {
+        pub method: String,
+        pub path: String,
+        pub cookie: Option<String>,
+        pub body: String,
+    }
+
+    pub struct Server {
+        pub base_url: String,
+        pub requests: Arc<Mutex<Vec<Request>>>,
+    }
+
+    impl Server {
+        /// Serves on a free local port; `handler` returns the status and body of each response.
+        pub fn start(handler: impl Fn(&Request) -> (u16, String) + Send + 'static) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let base_url = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let log = Arc::clone(&requests);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let mut stream = stream.unwrap();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let mut parts = line.split_whitespace();
+                    let method = parts.next().unwrap_or_default().to_string();
+                    let path = parts.next().unwrap_or_default().to_string();
+                    let (mut cookie, mut length) = (None, 0);
+                    loop {
+                        line.clear();
+                        reader.read_line(&mut line).unwrap();
+                        let Some((name, value)) = line.trim_end().split_once(": ") else {
+                            break;
+                        };
+                        match name.to_ascii_lowercase().as_str() {
+                            "cookie" => cookie = Some(value.to_string()),
+                            "content-length" => length = value.parse().unwrap(),
+                            _ => {}
+                        }
+                    }
+                    let mut body = vec![0; length];
+                    reader.read_exact(&mut body).unwrap();
+                    let request = Request {
+                        method,
+                        path,
+                        cookie,
+                        body: String::from_utf8(body).unwrap(),
+                    };
+                    let (status, body) = handler(&request);
+                    log.lock().unwrap().push(request);
+                    write!(
+                        stream,
+                        "HTTP/1.1 {status} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    )
+                    .unwrap();
+                }
+            });
+            Self { base_url, requests }
+        }
+
+        pub fn requests(&self) -> Vec<Request> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn throttle_waits_for_the_interval_since_the_last_request() {
+        let dir = std::env::temp_dir().join(format!("aoc-2022-throttle-{}", std::process::id()));
+        let throttle = Throttle::new(dir.join(".last-request"), Duration::from_millis(300));
+        assert_eq!(throttle.remaining(), Duration::ZERO);
+        throttle.wait().unwrap();
+        assert!(throttle.remaining() > Duration::from_millis(200));
+        let again = Throttle::new(dir.join(".last-request"), Duration::from_millis(300));
+        let start = std::time::Instant::now();
+        again.wait().unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn client_needs_a_session_token() {
+        let err = Client::new(&Config::default(), Path::new("inputs"))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("session"));
+    }
+}
@@ -0,0 +1,118 @@
+use std::borrow::Cow;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{bail, Result, WrapErr};
+
+/// Where the puzzle input of a day is read from, in order of preference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// An explicit file, `-` meaning stdin.
+    Path(PathBuf),
+    /// `<dir>/dayNN.txt`, falling back to the input embedded in the binary.
+    Directory(PathBuf),
+}
+
+impl Source {
+    pub fn from_args(input: Option<PathBuf>, inputs_dir: PathBuf) -> Self {
+        match input {
+            Some(path) => Source::Path(path),
+            None => Source::Directory(inputs_dir),
+        }
+    }
+}
+
+/// Brings puzzle text into the one shape every parser expects: no byte order mark, `\n` line
+/// endings and exactly one newline after the last non-blank line. Leading lines are kept, so
+/// line numbers in parse errors still match the file.
+pub fn normalize(input: &str) -> Cow<'_, str> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let end = input.trim_end_matches(['\n', '\r', ' ', '\t']).len();
+    let input = &input[..end];
+    if input.is_empty() {
+        return Cow::Borrowed(input);
+    }
+    let mut normalized = input.replace("\r\n", "\n");
+    normalized.push('\n');
+    Cow::Owned(normalized)
+}
+
+pub fn file_name(day: u8) -> String {
+    format!("day{day:02}.txt")
+}
+
+fn read_file(path: &Path) -> Result<String> {
+    if path == Path::new("-") {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .wrap_err("could not read input from stdin")?;
+        return Ok(input);
+    }
+    fs::read_to_string(path).wrap_err_with(|| format!("could not read input {}", path.display()))
+}
+
+pub fn load(day: u8, source: &Source, embedded: Option<&'static str>) -> Result<Cow<'static, str>> {
+    match source {
+        Source::Path(path) => Ok(Cow::Owned(read_file(path)?)),
+        Source::Directory(dir) => {
+            let path = dir.join(file_name(day));
+            if path.exists() {
+                return Ok(Cow::Owned(read_file(&path)?));
+            }
+            match embedded {
+                Some(input) => Ok(Cow::Borrowed(input)),
+                None => bail!("no input for day {day}, expected {}", path.display()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc-2022-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn normalizing_removes_boms_crlf_and_trailing_blank_lines() {
+        assert_eq!(normalize("a\nb\n"), "a\nb\n");
+        assert_eq!(normalize("a\nb"), "a\nb\n");
+        assert_eq!(normalize("\u{feff}a\r\nb\r\n\r\n"), "a\nb\n");
+        assert_eq!(normalize("a\n\n  \n\t\n"), "a\n");
+        assert_eq!(normalize("\n  indented\n\nb\n"), "\n  indented\n\nb\n");
+        assert_eq!(normalize("\u{feff}\r\n"), "");
+    }
+
+    #[test]
+    fn directory_input_is_preferred_over_embedded_one() {
+        let dir = temp_dir("inputs-dir");
+        fs::write(dir.join("day05.txt"), "from disk").unwrap();
+        let source = Source::Directory(dir.clone());
+        assert_eq!(load(5, &source, Some("embedded")).unwrap(), "from disk");
+        assert_eq!(load(6, &source, Some("embedded")).unwrap(), "embedded");
+        assert!(load(6, &source, None).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn explicit_path_is_never_replaced_by_embedded_input() {
+        let dir = temp_dir("explicit-path");
+        let path = dir.join("custom.txt");
+        fs::write(&path, "custom").unwrap();
+        assert_eq!(
+            load(1, &Source::Path(path.clone()), None).unwrap(),
+            "custom"
+        );
+        fs::remove_file(&path).unwrap();
+        assert!(load(1, &Source::Path(path), Some("embedded")).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
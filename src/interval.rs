@@ -0,0 +1,246 @@
+//! Inclusive integer intervals and sets of them.
+//!
+//! An [`IntervalSet`] keeps its intervals sorted, disjoint and apart from each other, so every
+//! operation works on the interval ends and never on the values in between.
+
+use std::fmt;
+
+/// The integers from `start` to `end`, both included. Never empty.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval {
+    start: i64,
+    end: i64,
+}
+
+impl Interval {
+    /// The interval from `start` to `end`, `None` when `end` comes before `start`.
+    pub fn new(start: i64, end: i64) -> Option<Self> {
+        (start <= end).then_some(Self { start, end })
+    }
+
+    pub fn start(self) -> i64 {
+        self.start
+    }
+
+    pub fn end(self) -> i64 {
+        self.end
+    }
+
+    /// Number of integers in the interval.
+    #[allow(clippy::len_without_is_empty)] // an interval always holds `start`
+    pub fn len(self) -> u64 {
+        self.start.abs_diff(self.end) + 1
+    }
+
+    pub fn contains(self, value: i64) -> bool {
+        self.start <= value && value <= self.end
+    }
+
+    /// Whether every value of `other` is also in `self`.
+    pub fn covers(self, other: Self) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub fn overlaps(self, other: Self) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    pub fn intersection(self, other: Self) -> Option<Self> {
+        Self::new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    /// The single interval holding both, when they overlap or sit next to each other.
+    pub fn merge(self, other: Self) -> Option<Self> {
+        let apart =
+            self.end.saturating_add(1) < other.start || other.end.saturating_add(1) < self.start;
+        (!apart).then(|| Self {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        })
+    }
+}
+
+impl fmt::Debug for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..={}", self.start, self.end)
+    }
+}
+
+/// A set of integers stored as the fewest intervals covering it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The intervals of the set, sorted and with a gap between any two of them.
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Number of integers in the set.
+    pub fn len(&self) -> u64 {
+        self.intervals.iter().map(|interval| interval.len()).sum()
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        let i = self
+            .intervals
+            .partition_point(|interval| interval.end < value);
+        self.intervals
+            .get(i)
+            .is_some_and(|interval| interval.contains(value))
+    }
+
+    /// Adds `interval`, merging it with every interval it overlaps or touches.
+    pub fn insert(&mut self, interval: Interval) {
+        let first = self
+            .intervals
+            .partition_point(|other| other.end.saturating_add(1) < interval.start);
+        let mut merged = interval;
+        let mut last = first;
+        while let Some(next) = self.intervals.get(last).and_then(|&o| merged.merge(o)) {
+            merged = next;
+            last += 1;
+        }
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        union.extend(other.intervals.iter().copied());
+        union
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.intervals
+            .iter()
+            .flat_map(|a| other.intervals.iter().filter_map(|&b| a.intersection(b)))
+            .collect()
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        self.intervals
+            .iter()
+            .flat_map(|&interval| {
+                let mut removed = IntervalSet::new();
+                removed.extend(
+                    other
+                        .intervals
+                        .iter()
+                        .filter_map(|&o| o.intersection(interval)),
+                );
+                removed.gaps(interval).collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// The runs of values in `within` that are not in the set, in order.
+    pub fn gaps(&self, within: Interval) -> impl Iterator<Item = Interval> + '_ {
+        let mut next = Some(within.start);
+        self.intervals
+            .iter()
+            .filter_map(move |&interval| interval.intersection(within))
+            .map(Some)
+            .chain([None])
+            .filter_map(move |interval| {
+                let from = next?;
+                match interval {
+                    Some(interval) => {
+                        next = interval.end.checked_add(1).filter(|&n| n <= within.end);
+                        Interval::new(from, interval.start - 1)
+                    }
+                    None => {
+                        next = None;
+                        Interval::new(from, within.end)
+                    }
+                }
+            })
+    }
+}
+
+impl Extend<Interval> for IntervalSet {
+    fn extend<I: IntoIterator<Item = Interval>>(&mut self, intervals: I) {
+        for interval in intervals {
+            self.insert(interval);
+        }
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(intervals: I) -> Self {
+        let mut set = Self::new();
+        set.extend(intervals);
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn interval(start: i64, end: i64) -> Interval {
+        Interval::new(start, end).unwrap()
+    }
+
+    fn set(intervals: &[(i64, i64)]) -> IntervalSet {
+        intervals.iter().map(|&(s, e)| interval(s, e)).collect()
+    }
+
+    fn pairs(set: &IntervalSet) -> Vec<(i64, i64)> {
+        set.intervals().iter().map(|i| (i.start, i.end)).collect()
+    }
+
+    #[test]
+    fn intervals_compare_by_their_ends() {
+        assert_eq!(Interval::new(3, 2), None);
+        let (a, b) = (interval(2, 8), interval(3, 7));
+        assert!(a.covers(b) && !b.covers(a));
+        assert!(a.overlaps(b));
+        assert_eq!(a.len(), 7);
+        assert_eq!(interval(1, 3).intersection(interval(4, 5)), None);
+        assert_eq!(interval(1, 3).merge(interval(4, 5)), Some(interval(1, 5)));
+        assert_eq!(interval(1, 3).merge(interval(5, 5)), None);
+    }
+
+    #[test]
+    fn inserting_merges_overlapping_and_adjacent_intervals() {
+        let mut s = set(&[(10, 12), (0, 2), (20, 25)]);
+        assert_eq!(pairs(&s), [(0, 2), (10, 12), (20, 25)]);
+        s.insert(interval(3, 11));
+        assert_eq!(pairs(&s), [(0, 12), (20, 25)]);
+        assert_eq!(s.len(), 19);
+        assert!(s.contains(12) && !s.contains(13) && s.contains(20));
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = set(&[(0, 10), (20, 30)]);
+        let b = set(&[(5, 25)]);
+        assert_eq!(pairs(&a.union(&b)), [(0, 30)]);
+        assert_eq!(pairs(&a.intersection(&b)), [(5, 10), (20, 25)]);
+        assert_eq!(pairs(&a.difference(&b)), [(0, 4), (26, 30)]);
+        assert_eq!(pairs(&b.difference(&a)), [(11, 19)]);
+        assert!(a.difference(&a).is_empty());
+    }
+
+    #[test]
+    fn gaps_are_the_uncovered_runs() {
+        let s = set(&[(0, 2), (5, 6), (9, 20)]);
+        let gaps: Vec<_> = s.gaps(interval(-1, 12)).collect();
+        assert_eq!(gaps, [interval(-1, -1), interval(3, 4), interval(7, 8)]);
+        let gaps: Vec<_> = s.gaps(interval(3, 25)).collect();
+        assert_eq!(gaps, [interval(3, 4), interval(7, 8), interval(21, 25)]);
+        assert_eq!(IntervalSet::new().gaps(interval(1, 2)).count(), 1);
+    }
+}
@@ -0,0 +1,48 @@
+//! Advent of Code 2022 solutions.
+//!
+//! Every day lives in `day_N::dayN`: a `DayN` type implementing [`solution::Solution`], which
+//! parses the puzzle input into the day's model and solves both parts from it, next to the
+//! model types themselves. [`runner`] runs days by number, and the remaining modules back the
+//! command line tool.
+
+extern crate core;
+
+pub mod cli;
+pub mod config;
+pub mod day_1;
+pub mod day_10;
+pub mod day_11;
+pub mod day_12;
+pub mod day_13;
+pub mod day_14;
+pub mod day_15;
+pub mod day_16;
+pub mod day_17;
+pub mod day_18;
+pub mod day_2;
+pub mod day_20;
+pub mod day_3;
+pub mod day_4;
+pub mod day_5;
+pub mod day_6;
+pub mod day_7;
+pub mod day_8;
+pub mod day_9;
+pub mod fetch;
+pub mod generate;
+pub mod geometry;
+pub mod grid;
+pub mod http;
+pub mod input;
+pub mod interval;
+pub mod output;
+pub mod params;
+pub mod parse;
+pub mod runner;
+pub mod scaffold;
+pub mod search;
+pub mod solution;
+pub mod submit;
+pub mod timing;
+pub mod trace;
+pub mod verify;
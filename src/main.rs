@@ -1,50 +1,200 @@
-extern crate core;
-
-use day_1::day1;
-use day_10::day10;
-use day_11::day11;
-use day_14::day14;
-use day_17::day17;
-use day_20::day20;
-use day_3::day3;
-use day_4::day4;
-use day_5::day5;
-use day_6::day6;
-use day_8::day8;
-use day_9::day9;
-
-use crate::day_15::day15;
-
-mod day_1;
-mod day_10;
-mod day_11;
-mod day_14;
-mod day_15;
-mod day_17;
-mod day_2;
-mod day_20;
-mod day_3;
-mod day_4;
-mod day_5;
-mod day_6;
-mod day_8;
-mod day_9;
-
-fn main() {
-    println!("======*****======= AoC 2022 Solutions ======*****=======");
-    println!();
+use std::fs;
+use std::io::{self, Write};
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::Parser;
+
+use aoc_2022_rust::cli::{
+    Cli, Command, FetchArgs, GenArgs, NewArgs, RunArgs, SubmitArgs, VerifyArgs,
+};
+use aoc_2022_rust::fetch::Fetched;
+use aoc_2022_rust::output::{Format, Record};
+use aoc_2022_rust::runner::Outcome;
+use aoc_2022_rust::submit::Response;
+use aoc_2022_rust::verify::Verdict;
+use aoc_2022_rust::{config, fetch, output, runner, scaffold, submit, timing, trace, verify};
+
+fn run(args: &RunArgs) -> bool {
+    let days = args.days.days();
+    let parts = args.parts();
+    let source = args.source();
+
+    if args.input.is_some() && days.len() != 1 {
+        eprintln!("--input can only be used when running a single day");
+        return false;
+    }
+    let mut params = match config::Config::load(&args.config) {
+        Ok(config) => config.params,
+        Err(err) => {
+            eprintln!("{err:#}");
+            return false;
+        }
+    };
+    params.extend(args.params.iter().cloned());
+    if args.trace {
+        trace::subscribe_globally(|event| eprintln!("day {:2}: {event}", event.day()));
+    }
+
+    if args.format == Format::Text {
+        println!("======*****======= AoC 2022 Solutions ======*****=======");
+        println!();
+        println!();
+    }
+    let outcomes = runner::run_days(
+        &days,
+        &source,
+        &params,
+        &parts,
+        args.iterations as usize,
+        args.jobs as usize,
+        |outcome| {
+            if args.format == Format::Text {
+                output::print_text(outcome);
+            }
+        },
+    );
+
+    // An explicitly requested part has to exist, `run all` only reports it.
+    let success = outcomes
+        .iter()
+        .all(|outcome| outcome.is_success(args.part.is_some()));
+    let records = || -> Vec<Record> {
+        outcomes
+            .iter()
+            .flat_map(|outcome| output::records(outcome, &parts))
+            .collect()
+    };
+    let written = match args.format {
+        Format::Text => {
+            if args.time || args.iterations > 1 {
+                let reports = outcomes.iter().filter_map(Outcome::report).collect();
+                print!("{}", timing::Report(reports));
+            }
+            Ok(())
+        }
+        Format::Json => output::write_json(io::stdout().lock(), &records()),
+        Format::Csv => output::write_csv(io::stdout().lock(), &records()),
+    };
+    if let Err(err) = written {
+        eprintln!("could not write results: {err:#}");
+        return false;
+    }
+    success
+}
+
+fn verify(args: &VerifyArgs) -> bool {
+    let manifest = match verify::Manifest::load(&args.manifest) {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            eprintln!("{err:#}");
+            return false;
+        }
+    };
+    let checks = verify::verify(&manifest, &args.days.days());
+    for check in &checks {
+        println!("{check}");
+    }
+    let count = |f: fn(&Verdict) -> bool| checks.iter().filter(|c| f(&c.verdict)).count();
+    let failed = count(|v| matches!(v, Verdict::Fail { .. }));
     println!();
-    // day1::run();
-    // day3::run();
-    // day4::run();
-    // day5::run();
-    // day6::run();
-    // day8::run();
-    // day9::run();
-    // day10::run();
-    // day11::run();
-    // day14::run();
-    day15::run();
-    // day17::run();
-    // day20::run();
+    println!(
+        "{} passed, {failed} failed, {} missing",
+        count(|v| *v == Verdict::Pass),
+        count(|v| *v == Verdict::Missing)
+    );
+    failed == 0
+}
+
+fn fetch(args: &FetchArgs) -> bool {
+    let fetched = config::Config::load(&args.config)
+        .and_then(|config| fetch::fetch(args.day, &config, &args.inputs_dir));
+    match fetched {
+        Ok(Fetched::Cached(path)) => {
+            println!("Day {} is already cached in {}", args.day, path.display())
+        }
+        Ok(Fetched::Downloaded(path)) => println!("Day {} saved to {}", args.day, path.display()),
+        Err(err) => {
+            eprintln!("could not fetch day {}: {err:#}", args.day);
+            return false;
+        }
+    }
+    true
+}
+
+fn submit(args: &SubmitArgs) -> bool {
+    let (day, part) = (args.day, args.part());
+    let submitted = config::Config::load(&args.config).and_then(|config| {
+        let answer = submit::answer(day, &args.source(), part)?;
+        println!("Day {day} part {part}, submitting: {answer}");
+        let mut log = submit::Log::load(&args.log)?;
+        submit::submit(day, part, &answer, &config, &args.inputs_dir, &mut log)
+    });
+    match submitted {
+        Ok(response) => {
+            println!("Day {day} part {part}: {response}");
+            response == Response::Correct
+        }
+        Err(err) => {
+            eprintln!("could not submit day {day} part {part}: {err:#}");
+            false
+        }
+    }
+}
+
+fn new_day(args: &NewArgs) -> bool {
+    match scaffold::scaffold(args.day, &args.root) {
+        Ok(files) => {
+            for file in files {
+                println!("wrote {}", file.display());
+            }
+            true
+        }
+        Err(err) => {
+            eprintln!("could not create day {}: {err:#}", args.day);
+            false
+        }
+    }
+}
+
+fn generate(args: &GenArgs) -> bool {
+    let Some(day) = runner::find(args.day) else {
+        eprintln!("day {} is not implemented, it has no generator", args.day);
+        return false;
+    };
+    let seed = args.seed.unwrap_or_else(|| {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_nanos() as u64);
+        eprintln!("seed {seed}");
+        seed
+    });
+    let input = day.generate(seed, args.size.unwrap_or(day.default_size));
+    let written = match &args.output {
+        Some(path) => fs::write(path, input),
+        None => io::stdout().lock().write_all(input.as_bytes()),
+    };
+    if let Err(err) = written {
+        eprintln!("could not write the input of day {}: {err}", args.day);
+        return false;
+    }
+    true
+}
+
+fn main() -> ExitCode {
+    color_eyre::install().expect("Unable to setup color eyre");
+    let cli = Cli::parse();
+    let success = match &cli.command {
+        Command::Run(args) => run(args),
+        Command::Verify(args) => verify(args),
+        Command::Fetch(args) => fetch(args),
+        Command::Submit(args) => submit(args),
+        Command::New(args) => new_day(args),
+        Command::Gen(args) => generate(args),
+    };
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
 }
@@ -0,0 +1,232 @@
+use std::io::Write;
+use std::time::Duration;
+
+use clap::ValueEnum;
+use color_eyre::eyre::Result;
+use serde::Serialize;
+
+use crate::runner::Outcome;
+use crate::solution::{Answer, Part};
+use crate::timing::Stats;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Ok,
+    Error,
+    Unimplemented,
+}
+
+/// One row of structured output: the result of a single part of a single day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Record {
+    pub day: u8,
+    pub part: u8,
+    pub status: Status,
+    pub answer: Option<String>,
+    /// Median over all iterations.
+    pub duration_ns: Option<u64>,
+    pub error: Option<String>,
+}
+
+fn nanos(durations: &[Duration]) -> Option<u64> {
+    Stats::new(durations).map(|stats| stats.median.as_nanos() as u64)
+}
+
+pub fn records(outcome: &Outcome, parts: &[Part]) -> Vec<Record> {
+    let day = outcome.day();
+    match outcome {
+        Outcome::Solved(report) => report
+            .parts
+            .iter()
+            .map(|part| {
+                let record = Record {
+                    day,
+                    part: part.part.number(),
+                    status: Status::Ok,
+                    answer: None,
+                    duration_ns: nanos(&part.durations),
+                    error: None,
+                };
+                match &part.answer {
+                    Answer::Unimplemented => Record {
+                        status: Status::Unimplemented,
+                        duration_ns: None,
+                        ..record
+                    },
+                    Answer::NoAnswer(reason) => Record {
+                        status: Status::Error,
+                        error: Some(reason.clone()),
+                        ..record
+                    },
+                    answer => Record {
+                        answer: Some(answer.to_string()),
+                        ..record
+                    },
+                }
+            })
+            .collect(),
+        Outcome::Failed { error, .. } => parts
+            .iter()
+            .map(|part| Record {
+                day,
+                part: part.number(),
+                status: Status::Error,
+                answer: None,
+                duration_ns: None,
+                error: Some(error.clone()),
+            })
+            .collect(),
+        Outcome::Unimplemented { .. } => parts
+            .iter()
+            .map(|part| Record {
+                day,
+                part: part.number(),
+                status: Status::Unimplemented,
+                answer: None,
+                duration_ns: None,
+                error: None,
+            })
+            .collect(),
+    }
+}
+
+pub fn print_text(outcome: &Outcome) {
+    let day = outcome.day();
+    println!("=== Day {day} ===");
+    match outcome {
+        Outcome::Solved(report) => {
+            for part in &report.parts {
+                match &part.answer {
+                    Answer::NoAnswer(reason) => {
+                        println!("Day {day} part {} failed: {reason}", part.part)
+                    }
+                    answer => println!("Day {day} part {}, solution: {answer}", part.part),
+                }
+            }
+        }
+        Outcome::Failed { error, .. } => println!("Day {day} failed: {error}"),
+        Outcome::Unimplemented { .. } => println!("Day {day} is not implemented yet"),
+    }
+    println!("=============");
+    println!();
+}
+
+pub fn write_json(mut writer: impl Write, records: &[Record]) -> Result<()> {
+    serde_json::to_writer_pretty(&mut writer, records)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+pub fn write_csv(writer: impl Write, records: &[Record]) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for record in records {
+        writer.serialize(record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::runner::{DayReport, PartReport};
+
+    fn outcomes() -> Vec<Outcome> {
+        vec![
+            Outcome::Solved(DayReport {
+                day: 10,
+                parse: vec![Duration::from_nanos(5)],
+                parts: vec![
+                    PartReport {
+                        part: Part::One,
+                        answer: Answer::Text("a, \"b\"".to_string()),
+                        durations: vec![Duration::from_nanos(30), Duration::from_nanos(10)],
+                    },
+                    PartReport {
+                        part: Part::Two,
+                        answer: Answer::Unimplemented,
+                        durations: vec![Duration::from_nanos(1)],
+                    },
+                ],
+            }),
+            Outcome::Failed {
+                day: 3,
+                error: "bad input".to_string(),
+            },
+        ]
+    }
+
+    fn all_records() -> Vec<Record> {
+        outcomes()
+            .iter()
+            .flat_map(|outcome| records(outcome, &Part::ALL))
+            .collect()
+    }
+
+    #[test]
+    fn csv_output_has_one_row_per_part() {
+        let mut out = Vec::new();
+        write_csv(&mut out, &all_records()).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "day,part,status,answer,duration_ns,error\n\
+             10,1,ok,\"a, \"\"b\"\"\",20,\n\
+             10,2,unimplemented,,,\n\
+             3,1,error,,,bad input\n\
+             3,2,error,,,bad input\n"
+        );
+    }
+
+    #[test]
+    fn json_output_uses_lowercase_status() {
+        let mut out = Vec::new();
+        write_json(&mut out, &all_records()[..1]).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!([{
+                "day": 10,
+                "part": 1,
+                "status": "ok",
+                "answer": "a, \"b\"",
+                "duration_ns": 20,
+                "error": null,
+            }])
+        );
+    }
+
+    #[test]
+    fn part_without_answer_is_an_error() {
+        let outcome = Outcome::Solved(DayReport {
+            day: 12,
+            parse: vec![Duration::from_nanos(5)],
+            parts: vec![PartReport {
+                part: Part::One,
+                answer: Answer::NoAnswer("E cannot be reached".to_string()),
+                durations: vec![Duration::from_nanos(7)],
+            }],
+        });
+        assert_eq!(
+            records(&outcome, &[Part::One]),
+            vec![Record {
+                day: 12,
+                part: 1,
+                status: Status::Error,
+                answer: None,
+                duration_ns: Some(7),
+                error: Some("E cannot be reached".to_string()),
+            }]
+        );
+        assert!(!outcome.is_success(false));
+    }
+}
@@ -0,0 +1,222 @@
+//! Puzzle parameters: the numbers a puzzle fixes, such as a count of rounds or the row to scan.
+//!
+//! Every day declares its parameters as a [`Solution::Params`] struct whose `Default` holds the
+//! values of the puzzle. [`Overrides`] replace some of them, read from the `[params.dayNN]`
+//! tables of `aoc.toml` or given as `--param dayNN.name=value`, to run what-if experiments.
+//!
+//! [`Solution::Params`]: crate::solution::Solution::Params
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use color_eyre::eyre::{bail, eyre, Error, Result};
+use serde::de::{self, Deserialize, Deserializer, IgnoredAny};
+
+/// Checks on parameter values that deserialize fine but that a day cannot be run with, such as
+/// a negative size, so that a bad override is reported instead of making the day panic.
+pub trait Validate {
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Parameters of a day that has none; any override is an error.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoParams;
+
+impl<'de> Deserialize<'de> for NoParams {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let params = BTreeMap::<String, IgnoredAny>::deserialize(deserializer)?;
+        match params.keys().next() {
+            Some(name) => Err(de::Error::custom(format!(
+                "unknown parameter `{name}`, this day has none"
+            ))),
+            None => Ok(NoParams),
+        }
+    }
+}
+
+impl Validate for NoParams {}
+
+/// One parameter set on the command line as `dayNN.name=value`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment {
+    pub day: u8,
+    pub name: String,
+    pub value: toml::Value,
+}
+
+/// A TOML value, anything that is not one being taken as a bare string.
+fn parse_value(value: &str) -> toml::Value {
+    format!("value = {value}")
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(value.to_string()))
+}
+
+/// Day of a `dayNN` (or plain `NN`) key.
+fn parse_day_key(key: &str) -> Result<u8> {
+    let number = key.strip_prefix("day").unwrap_or(key);
+    number
+        .parse()
+        .map_err(|_| eyre!("expected dayNN, got {key:?}"))
+}
+
+impl FromStr for Assignment {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((key, value)) = s.split_once('=') else {
+            bail!("expected dayNN.name=value, got {s:?}");
+        };
+        let Some((day, name)) = key.trim().split_once('.') else {
+            bail!("expected dayNN.name=value, got {s:?}");
+        };
+        Ok(Self {
+            day: parse_day_key(day)?,
+            name: name.to_string(),
+            value: parse_value(value.trim()),
+        })
+    }
+}
+
+/// Parameters replacing the defaults, by day.
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize)]
+#[serde(try_from = "BTreeMap<String, toml::Table>")]
+pub struct Overrides {
+    days: BTreeMap<u8, toml::Table>,
+}
+
+impl TryFrom<BTreeMap<String, toml::Table>> for Overrides {
+    type Error = String;
+
+    fn try_from(tables: BTreeMap<String, toml::Table>) -> Result<Self, String> {
+        let days = tables
+            .into_iter()
+            .map(|(key, table)| Ok((parse_day_key(&key).map_err(|e| e.to_string())?, table)))
+            .collect::<Result<_, String>>()?;
+        Ok(Self { days })
+    }
+}
+
+impl Overrides {
+    /// The overridden parameters of `day`, empty when there are none.
+    pub fn day(&self, day: u8) -> toml::Table {
+        self.days.get(&day).cloned().unwrap_or_default()
+    }
+
+    pub fn set(&mut self, assignment: Assignment) {
+        self.days
+            .entry(assignment.day)
+            .or_default()
+            .insert(assignment.name, assignment.value);
+    }
+}
+
+impl Extend<Assignment> for Overrides {
+    fn extend<I: IntoIterator<Item = Assignment>>(&mut self, assignments: I) {
+        for assignment in assignments {
+            self.set(assignment);
+        }
+    }
+}
+
+/// The parameters of a day, `overrides` replacing its defaults.
+pub fn resolve<P: Default + de::DeserializeOwned + Validate>(overrides: &toml::Table) -> Result<P> {
+    if overrides.is_empty() {
+        return Ok(P::default());
+    }
+    let params: P = toml::Value::Table(overrides.clone())
+        .try_into()
+        .map_err(|err| eyre!("invalid parameters: {}", err.to_string().trim_end()))?;
+    params
+        .validate()
+        .map_err(|err| eyre!("invalid parameters: {err}"))?;
+    Ok(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(default, deny_unknown_fields)]
+    struct Params {
+        rounds: u32,
+        cycles: Vec<i32>,
+    }
+
+    impl Default for Params {
+        fn default() -> Self {
+            Self {
+                rounds: 20,
+                cycles: vec![20, 60],
+            }
+        }
+    }
+
+    impl Validate for Params {
+        fn validate(&self) -> Result<()> {
+            if self.rounds == 0 {
+                bail!("rounds must be at least 1");
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn assignments_parse_toml_values() {
+        let assignment: Assignment = "day11.rounds=30".parse().unwrap();
+        assert_eq!(
+            assignment,
+            Assignment {
+                day: 11,
+                name: "rounds".to_string(),
+                value: toml::Value::Integer(30)
+            }
+        );
+        let assignment: Assignment = "5.cycles = [1, 2]".parse().unwrap();
+        assert_eq!((assignment.day, assignment.name.as_str()), (5, "cycles"));
+        let assignment: Assignment = "5.name=bare".parse().unwrap();
+        assert_eq!(assignment.value, toml::Value::String("bare".to_string()));
+        assert!("rounds=30".parse::<Assignment>().is_err());
+        assert!("dayx.rounds=30".parse::<Assignment>().is_err());
+    }
+
+    #[test]
+    fn overrides_replace_only_the_parameters_they_name() {
+        let mut overrides: Overrides =
+            toml::from_str("[day11]\nrounds = 5\n[day12]\nother = 1").unwrap();
+        overrides.extend(["11.cycles=[7]".parse().unwrap()]);
+        assert_eq!(
+            resolve::<Params>(&overrides.day(11)).unwrap(),
+            Params {
+                rounds: 5,
+                cycles: vec![7]
+            }
+        );
+        assert_eq!(
+            resolve::<Params>(&overrides.day(3)).unwrap(),
+            Params::default()
+        );
+        let err = resolve::<Params>(&overrides.day(12)).unwrap_err();
+        assert!(err.to_string().contains("unknown field `other`"), "{err}");
+        assert!(resolve::<NoParams>(&overrides.day(11)).is_err());
+        assert!(toml::from_str::<Overrides>("[weekday]\na = 1").is_err());
+    }
+
+    #[test]
+    fn overrides_are_validated() {
+        let mut overrides = Overrides::default();
+        overrides.extend(["11.rounds=0".parse().unwrap()]);
+        let err = resolve::<Params>(&overrides.day(11)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid parameters: rounds must be at least 1"
+        );
+    }
+}
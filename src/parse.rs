@@ -0,0 +1,118 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Malformed puzzle input, pointing at the offending text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u8,
+    /// 1-based, 0 when the error is not tied to a single line.
+    pub line: usize,
+    /// 1-based, counted in bytes.
+    pub column: usize,
+    pub text: String,
+    pub reason: String,
+}
+
+impl ParseError {
+    /// Error for `text`, located at its first column until the caller knows better.
+    pub fn new(reason: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            day: 0,
+            line: 0,
+            column: 1,
+            text: text.into(),
+            reason: reason.into(),
+        }
+    }
+
+    pub fn at_column(mut self, column: usize) -> Self {
+        self.column = column;
+        self
+    }
+
+    /// Moves the column of an error raised for `part` so that it is relative to `whole`,
+    /// `part` being a slice of `whole`.
+    pub fn within(mut self, whole: &str, part: &str) -> Self {
+        self.column += offset_of(whole, part);
+        self
+    }
+
+    pub fn at_line(mut self, day: u8, line: usize) -> Self {
+        self.day = day;
+        self.line = line;
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day {}, line {}, column {}: {}: {:?}",
+            self.day, self.line, self.column, self.reason, self.text
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Byte offset of `part` inside `whole`, 0 if it is not a slice of it.
+pub fn offset_of(whole: &str, part: &str) -> usize {
+    let offset = (part.as_ptr() as usize).wrapping_sub(whole.as_ptr() as usize);
+    if offset <= whole.len() {
+        offset
+    } else {
+        0
+    }
+}
+
+pub fn parse_num<T: FromStr>(text: &str, what: &str) -> Result<T, ParseError> {
+    text.parse::<T>()
+        .map_err(|_| ParseError::new(format!("invalid {what}"), text))
+}
+
+/// Parses each of `lines` with `parse`, tagging errors with the day and 1-based line number.
+pub fn parse_lines<'a, T>(
+    day: u8,
+    lines: impl IntoIterator<Item = &'a str>,
+    mut parse: impl FnMut(&'a str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(index, line)| parse(line).map_err(|err| err.at_line(day, index + 1)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn errors_are_located_by_line_and_column() {
+        let input = "1,2\n3,x\n";
+        let err = parse_lines(42, input.lines(), |line| {
+            let (a, b) = line.split_once(',').unwrap();
+            Ok((
+                parse_num::<u8>(a, "number").map_err(|e| e.within(line, a))?,
+                parse_num::<u8>(b, "number").map_err(|e| e.within(line, b))?,
+            ))
+        })
+        .unwrap_err();
+        assert_eq!((err.day, err.line, err.column), (42, 2, 3));
+        assert_eq!(
+            err.to_string(),
+            "day 42, line 2, column 3: invalid number: \"x\""
+        );
+    }
+
+    #[test]
+    fn offset_of_foreign_slice_is_zero() {
+        let line = "abc def";
+        let other = String::from("def");
+        assert_eq!(offset_of(line, &line[4..]), 4);
+        assert_eq!(offset_of(line, &other), 0);
+    }
+}
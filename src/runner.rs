@@ -0,0 +1,433 @@
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Once};
+use std::thread;
+use std::time::Duration;
+
+use crate::day_1::day1;
+use crate::day_10::day10;
+use crate::day_11::day11;
+use crate::day_12::day12;
+use crate::day_13::day13;
+use crate::day_14::day14;
+use crate::day_15::day15;
+use crate::day_16::day16;
+use crate::day_17::day17;
+use crate::day_18::day18;
+use crate::day_2::day2;
+use crate::day_20::day20;
+use crate::day_3::day3;
+use crate::day_4::day4;
+use crate::day_5::day5;
+use crate::day_6::day6;
+use crate::day_7::day7;
+use crate::day_8::day8;
+use crate::day_9::day9;
+use crate::generate::{Generate, Rng};
+use crate::input::{self, Source};
+use crate::params::{self, Overrides};
+use crate::solution::{Answer, Part, Solution};
+use crate::timing::timed;
+
+type SolveFn = fn(&str, &toml::Table, &[Part], usize) -> color_eyre::Result<DayReport>;
+type GenerateFn = fn(&mut Rng, usize) -> String;
+
+pub struct PartReport {
+    pub part: Part,
+    pub answer: Answer,
+    pub durations: Vec<Duration>,
+}
+
+/// Answers of a day together with the time taken by every parse and part run.
+pub struct DayReport {
+    pub day: u8,
+    pub parse: Vec<Duration>,
+    pub parts: Vec<PartReport>,
+}
+
+/// A registered day: its number, the embedded puzzle input (if compiled in), a type-erased
+/// solver and an input generator.
+pub struct Day {
+    pub day: u8,
+    pub embedded_input: Option<&'static str>,
+    /// Size of a generated input when none is asked for.
+    pub default_size: usize,
+    solve: SolveFn,
+    generate: GenerateFn,
+}
+
+impl Day {
+    /// Parses and solves `input` `iterations` times (at least once), timing each step separately.
+    /// `params` overrides some of the day's parameters, an empty table keeping the defaults.
+    pub fn solve(
+        &self,
+        input: &str,
+        params: &toml::Table,
+        parts: &[Part],
+        iterations: usize,
+    ) -> color_eyre::Result<DayReport> {
+        (self.solve)(input, params, parts, iterations.max(1))
+    }
+
+    /// A random input of about `size` units, always the same for the same `seed`.
+    pub fn generate(&self, seed: u64, size: usize) -> String {
+        (self.generate)(&mut Rng::new(seed), size)
+    }
+}
+
+fn solve<S: Solution>(
+    input: &str,
+    params: &toml::Table,
+    parts: &[Part],
+    iterations: usize,
+) -> color_eyre::Result<DayReport> {
+    let params = params::resolve::<S::Params>(params)?;
+    let mut report = DayReport {
+        day: S::DAY,
+        parse: Vec::with_capacity(iterations),
+        parts: parts
+            .iter()
+            .map(|&part| PartReport {
+                part,
+                answer: Answer::Unimplemented,
+                durations: Vec::with_capacity(iterations),
+            })
+            .collect(),
+    };
+    for _ in 0..iterations {
+        let (parsed, elapsed) = timed(|| S::read(input));
+        let parsed = parsed?;
+        report.parse.push(elapsed);
+        for part in &mut report.parts {
+            let (answer, elapsed) = timed(|| S::solve(&parsed, &params, part.part));
+            part.answer = answer;
+            part.durations.push(elapsed);
+        }
+    }
+    Ok(report)
+}
+
+/// An empty `input.txt`, as left by `new` until the puzzle input is fetched, counts as no input
+/// at all so that the day reports it missing instead of solving nothing.
+#[cfg(feature = "embedded-inputs")]
+macro_rules! embedded {
+    ($input:expr) => {
+        if $input.is_empty() {
+            None
+        } else {
+            Some($input)
+        }
+    };
+}
+
+#[cfg(not(feature = "embedded-inputs"))]
+macro_rules! embedded {
+    ($input:expr) => {{
+        let _ = $input;
+        None
+    }};
+}
+
+macro_rules! day {
+    ($module:ident :: $solution:ident) => {
+        Day {
+            day: <$module::$solution as Solution>::DAY,
+            embedded_input: embedded!($module::INPUT),
+            default_size: <$module::$solution as Generate>::DEFAULT_SIZE,
+            solve: solve::<$module::$solution>,
+            generate: <$module::$solution as Generate>::generate,
+        }
+    };
+}
+
+pub const DAYS: &[Day] = &[
+    day!(day1::Day1),
+    day!(day2::Day2),
+    day!(day3::Day3),
+    day!(day4::Day4),
+    day!(day5::Day5),
+    day!(day6::Day6),
+    day!(day7::Day7),
+    day!(day8::Day8),
+    day!(day9::Day9),
+    day!(day10::Day10),
+    day!(day11::Day11),
+    day!(day12::Day12),
+    day!(day13::Day13),
+    day!(day14::Day14),
+    day!(day15::Day15),
+    day!(day16::Day16),
+    day!(day17::Day17),
+    day!(day18::Day18),
+    day!(day20::Day20),
+];
+
+pub fn find(day: u8) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.day == day)
+}
+
+/// What happened when a single day was run.
+pub enum Outcome {
+    Solved(DayReport),
+    Failed { day: u8, error: String },
+    Unimplemented { day: u8 },
+}
+
+impl Outcome {
+    pub fn day(&self) -> u8 {
+        match self {
+            Outcome::Solved(report) => report.day,
+            Outcome::Failed { day, .. } | Outcome::Unimplemented { day } => *day,
+        }
+    }
+
+    pub fn report(&self) -> Option<&DayReport> {
+        match self {
+            Outcome::Solved(report) => Some(report),
+            _ => None,
+        }
+    }
+
+    /// A day succeeds when every part it ran has an answer; unimplemented parts only count when
+    /// `strict`.
+    pub fn is_success(&self, strict: bool) -> bool {
+        match self {
+            Outcome::Solved(report) => report.parts.iter().all(|p| match p.answer {
+                Answer::NoAnswer(_) => false,
+                Answer::Unimplemented => !strict,
+                _ => true,
+            }),
+            _ => false,
+        }
+    }
+}
+
+pub fn run_day(
+    day: u8,
+    source: &Source,
+    params: &Overrides,
+    parts: &[Part],
+    iterations: usize,
+) -> Outcome {
+    let Some(solver) = find(day) else {
+        return Outcome::Unimplemented { day };
+    };
+    match input::load(day, source, solver.embedded_input)
+        .and_then(|input| solver.solve(&input, &params.day(day), parts, iterations))
+    {
+        Ok(report) => Outcome::Solved(report),
+        Err(err) => Outcome::Failed {
+            day,
+            error: format!("{err:#}"),
+        },
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Runs `work(0..len)` on `jobs` worker threads and hands the results to `on_result` in index
+/// order, each as soon as it and all the ones before it are done.
+fn in_order_pool<T: Send>(
+    len: usize,
+    jobs: usize,
+    work: impl Fn(usize) -> T + Sync,
+    mut on_result: impl FnMut(&T),
+) -> Vec<T> {
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    let mut results = Vec::with_capacity(len);
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, len.max(1)) {
+            let sender = sender.clone();
+            let (next, work) = (&next, &work);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= len || sender.send((index, work(index))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        let mut pending = BTreeMap::new();
+        for (index, result) in receiver {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&results.len()) {
+                on_result(&result);
+                results.push(result);
+            }
+        }
+    });
+    results
+}
+
+thread_local! {
+    /// Whether this thread is inside `catch_panic`, whose caller reports the panic itself.
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+static QUIET_HOOK: Once = Once::new();
+
+/// Runs `run`, turning a panic into an error that says what the panic was. The panic hook in
+/// place when this is first called keeps handling panics, except those caught here, which would
+/// otherwise be printed with a backtrace on top of being reported.
+pub fn catch_panic<T>(run: impl FnOnce() -> T) -> Result<T, String> {
+    QUIET_HOOK.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !CATCHING.get() {
+                hook(info);
+            }
+        }));
+    });
+    let outer = CATCHING.replace(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(run));
+    CATCHING.set(outer);
+    result.map_err(|payload| format!("panicked: {}", panic_message(payload.as_ref())))
+}
+
+/// Turns a panic while running `day` into a failed outcome.
+fn catching(day: u8, run: impl FnOnce() -> Outcome) -> Outcome {
+    catch_panic(run).unwrap_or_else(|error| Outcome::Failed { day, error })
+}
+
+/// Runs `days` on a pool of `jobs` worker threads. A day that panics becomes a failed outcome
+/// instead of taking the others down, and `on_outcome` sees the outcomes in the order of `days`.
+pub fn run_days(
+    days: &[u8],
+    source: &Source,
+    params: &Overrides,
+    parts: &[Part],
+    iterations: usize,
+    jobs: usize,
+    on_outcome: impl FnMut(&Outcome),
+) -> Vec<Outcome> {
+    in_order_pool(
+        days.len(),
+        jobs,
+        |index| {
+            let day = days[index];
+            catching(day, || run_day(day, source, params, parts, iterations))
+        },
+        on_outcome,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn parallel_run_keeps_day_order() {
+        let days = [20, 1, 25, 5, 3];
+        let source = Source::Directory(PathBuf::from("no-such-inputs-dir"));
+        let mut seen = Vec::new();
+        let outcomes = run_days(
+            &days,
+            &source,
+            &Overrides::default(),
+            &Part::ALL,
+            1,
+            4,
+            |o| seen.push(o.day()),
+        );
+        assert_eq!(seen, days);
+        assert_eq!(outcomes.iter().map(Outcome::day).collect::<Vec<_>>(), days);
+        assert!(matches!(outcomes[2], Outcome::Unimplemented { day: 25 }));
+    }
+
+    fn answers(day: &Day, input: &str) -> Vec<String> {
+        let report = day
+            .solve(input, &toml::Table::new(), &Part::ALL, 1)
+            .unwrap_or_else(|err| panic!("day {}: {err}\n{input:?}", day.day));
+        report
+            .parts
+            .iter()
+            .map(|part| part.answer.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn every_day_accepts_boms_crlf_and_trailing_newlines() {
+        for day in DAYS {
+            // Examples are small and fast to solve, so they are used where there is one.
+            let path = format!(
+                "{}/src/day_{}/test_input.txt",
+                env!("CARGO_MANIFEST_DIR"),
+                day.day
+            );
+            let lines = std::fs::read_to_string(path).unwrap_or_else(|_| day.generate(3, 20));
+            let lines = lines.trim_end();
+            let expected = answers(day, lines);
+            for input in [
+                format!("{lines}\n"),
+                format!("{lines}\n\n\n"),
+                format!("\u{feff}{lines}\n"),
+                format!("{}\r\n", lines.replace('\n', "\r\n")),
+                format!("\u{feff}{}\r\n\r\n", lines.replace('\n', "\r\n")),
+            ] {
+                assert_eq!(answers(day, &input), expected, "day {} {input:?}", day.day);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_inputs_are_not_embedded() {
+        for day in DAYS {
+            assert_ne!(day.embedded_input, Some(""), "day {}", day.day);
+        }
+        let outcome = run_day(
+            7,
+            &Source::Directory(PathBuf::from("no-such-inputs-dir")),
+            &Overrides::default(),
+            &Part::ALL,
+            1,
+        );
+        match outcome {
+            Outcome::Failed { error, .. } => {
+                assert!(error.starts_with("no input for day 7"), "{error}")
+            }
+            _ => panic!("expected day 7 to fail for lack of input"),
+        }
+    }
+
+    #[test]
+    fn panicking_day_does_not_stop_the_others() {
+        let outcomes = in_order_pool(
+            4,
+            2,
+            |index| {
+                let day = index as u8;
+                catching(day, || {
+                    if day == 1 {
+                        panic!("boom");
+                    }
+                    Outcome::Unimplemented { day }
+                })
+            },
+            |_| {},
+        );
+        assert_eq!(outcomes.len(), 4);
+        match &outcomes[1] {
+            Outcome::Failed { day, error } => {
+                assert_eq!((*day, error.as_str()), (1, "panicked: boom"))
+            }
+            _ => panic!("expected day 1 to fail"),
+        }
+        assert!(matches!(outcomes[3], Outcome::Unimplemented { day: 3 }));
+    }
+}
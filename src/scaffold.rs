@@ -0,0 +1,163 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{bail, eyre, Result, WrapErr};
+use regex::Regex;
+
+const TEMPLATE: &str = include_str!("../templates/day.rs.tmpl");
+
+/// How the lines of a registration list are kept ordered.
+#[derive(Debug, Clone, Copy)]
+enum Order {
+    /// Path segment by segment, the way rustfmt sorts `mod` and `use` items.
+    Path,
+    /// By day number.
+    Day,
+}
+
+/// Adds `line` to the block of consecutive lines matching `pattern`, whose first capture is the
+/// day number, keeping the block in `order`.
+fn register(source: &str, pattern: &str, line: String, order: Order) -> Result<String> {
+    let re = Regex::new(pattern).unwrap();
+    let mut lines: Vec<String> = source.lines().map(str::to_string).collect();
+    let start = lines
+        .iter()
+        .position(|l| re.is_match(l))
+        .ok_or_else(|| eyre!("no line matching {pattern:?}"))?;
+    let end = start + lines[start..].iter().take_while(|l| re.is_match(l)).count();
+
+    let day = |l: &String| -> u8 { re.captures(l).unwrap()[1].parse().unwrap() };
+    let mut block = lines[start..end].to_vec();
+    block.push(line);
+    match order {
+        Order::Path => block.sort_by_key(|l| {
+            l.trim_end_matches(';')
+                .split("::")
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        }),
+        Order::Day => block.sort_by_key(day),
+    }
+    lines.splice(start..end, block);
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Creates `src/day_N` from the template under `root` and registers it with `lib.rs` and the
+/// runner. Returns every file created or changed.
+pub fn scaffold(day: u8, root: &Path) -> Result<Vec<PathBuf>> {
+    let src = root.join("src");
+    let dir = src.join(format!("day_{day}"));
+    if dir.exists() {
+        bail!("day {day} already exists in {}", dir.display());
+    }
+
+    // Everything is worked out before writing anything, so a failure leaves the tree untouched.
+    let lib_rs = src.join("lib.rs");
+    let runner_rs = src.join("runner.rs");
+    let read = |path: &Path| {
+        fs::read_to_string(path).wrap_err_with(|| format!("could not read {}", path.display()))
+    };
+    let lib = register(
+        &read(&lib_rs)?,
+        r"^pub mod day_(\d+);$",
+        format!("pub mod day_{day};"),
+        Order::Path,
+    )?;
+    let runner = read(&runner_rs)?;
+    let runner = register(
+        &runner,
+        r"^use crate::day_(\d+)::day\d+;$",
+        format!("use crate::day_{day}::day{day};"),
+        Order::Path,
+    )?;
+    let runner = register(
+        &runner,
+        r"^    day!\(day(\d+)::Day\d+\),$",
+        format!("    day!(day{day}::Day{day}),"),
+        Order::Day,
+    )?;
+
+    let files = [
+        (dir.join("mod.rs"), format!("pub mod day{day};\n")),
+        (
+            dir.join(format!("day{day}.rs")),
+            TEMPLATE.replace("{{DAY}}", &day.to_string()),
+        ),
+        (dir.join("input.txt"), String::new()),
+        (dir.join("test_input.txt"), String::new()),
+        (lib_rs, lib),
+        (runner_rs, runner),
+    ];
+    fs::create_dir_all(&dir)?;
+    for (path, contents) in &files {
+        fs::write(path, contents)
+            .wrap_err_with(|| format!("could not write {}", path.display()))?;
+    }
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const LIB: &str =
+        "pub mod cli;\npub mod day_1;\npub mod day_10;\npub mod day_2;\npub mod input;\n";
+    const RUNNER: &str = "use crate::day_1::day1;\n\
+                          use crate::day_10::day10;\n\
+                          use crate::day_2::day2;\n\
+                          use crate::input;\n\
+                          \n\
+                          pub const DAYS: &[Day] = &[\n    \
+                              day!(day1::Day1),\n    \
+                              day!(day2::Day2),\n    \
+                              day!(day10::Day10),\n\
+                          ];\n";
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("aoc-2022-{name}-{}", std::process::id()));
+        fs::create_dir_all(root.join("src/day_2")).unwrap();
+        fs::write(root.join("src/lib.rs"), LIB).unwrap();
+        fs::write(root.join("src/runner.rs"), RUNNER).unwrap();
+        root
+    }
+
+    #[test]
+    fn new_day_is_created_and_registered_in_order() {
+        let root = temp_root("scaffold");
+        let files = scaffold(3, &root).unwrap();
+        assert_eq!(files.len(), 6);
+
+        assert_eq!(
+            fs::read_to_string(root.join("src/lib.rs")).unwrap(),
+            "pub mod cli;\npub mod day_1;\npub mod day_10;\npub mod day_2;\npub mod day_3;\npub mod input;\n"
+        );
+        let runner = fs::read_to_string(root.join("src/runner.rs")).unwrap();
+        assert!(
+            runner.contains("use crate::day_2::day2;\nuse crate::day_3::day3;\nuse crate::input;")
+        );
+        assert!(
+            runner.contains("day!(day2::Day2),\n    day!(day3::Day3),\n    day!(day10::Day10),")
+        );
+
+        let module = fs::read_to_string(root.join("src/day_3/day3.rs")).unwrap();
+        assert!(module.contains("impl Solution for Day3 {\n    const DAY: u8 = 3;"));
+        assert!(!module.contains("{{"));
+        assert_eq!(
+            fs::read_to_string(root.join("src/day_3/input.txt")).unwrap(),
+            ""
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn existing_day_is_never_overwritten() {
+        let root = temp_root("scaffold-existing");
+        let err = scaffold(2, &root).unwrap_err();
+        assert!(err.to_string().contains("already exists"), "{err}");
+        assert_eq!(fs::read_to_string(root.join("src/lib.rs")).unwrap(), LIB);
+        assert!(!root.join("src/day_2/mod.rs").exists());
+        fs::remove_dir_all(root).unwrap();
+    }
+}
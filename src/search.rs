@@ -0,0 +1,102 @@
+//! Breadth-first search over implicit graphs.
+//!
+//! A graph is given by a `neighbours` function from a node to the nodes one step away, so the
+//! same search serves grids, valve networks or any other state space with unit-cost steps.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+/// A shortest path from any of `starts` to a node satisfying `is_goal`, both ends included,
+/// or `None` when no goal can be reached.
+pub fn shortest_path<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    // The node each reached node was first reached from, `None` for the starts.
+    let mut came_from: HashMap<N, Option<N>> = HashMap::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if let Entry::Vacant(entry) = came_from.entry(start.clone()) {
+            entry.insert(None);
+            queue.push_back(start);
+        }
+    }
+    while let Some(node) = queue.pop_front() {
+        if is_goal(&node) {
+            let mut path = vec![node];
+            while let Some(Some(previous)) = came_from.get(path.last().unwrap()) {
+                path.push(previous.clone());
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for next in neighbours(&node) {
+            if let Entry::Vacant(entry) = came_from.entry(next.clone()) {
+                entry.insert(Some(node.clone()));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Number of steps from `start` to every node reachable from it, `start` itself included.
+pub fn distances<N, I>(start: N, mut neighbours: impl FnMut(&N) -> I) -> HashMap<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        let distance = distances[&node] + 1;
+        for next in neighbours(&node) {
+            if let Entry::Vacant(entry) = distances.entry(next.clone()) {
+                entry.insert(distance);
+                queue.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    /// 0 -> 1 -> 2 -> 3 and a shortcut 0 -> 4 -> 3; 5 only leads to 0.
+    fn graph(node: &u32) -> Vec<u32> {
+        match node {
+            0 => vec![1, 4],
+            1 => vec![2],
+            2 => vec![3],
+            4 => vec![3],
+            5 => vec![0],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn finds_a_shortest_path_from_the_nearest_start() {
+        assert_eq!(shortest_path([0], graph, |&n| n == 3), Some(vec![0, 4, 3]));
+        assert_eq!(shortest_path([0, 2], graph, |&n| n == 3), Some(vec![2, 3]));
+        assert_eq!(shortest_path([3], graph, |&n| n == 3), Some(vec![3]));
+        assert_eq!(shortest_path([1], graph, |&n| n == 0), None);
+    }
+
+    #[test]
+    fn distances_cover_the_reachable_nodes() {
+        let distances = distances(5, graph);
+        assert_eq!(distances.len(), 6);
+        assert_eq!((distances[&5], distances[&0], distances[&3]), (0, 1, 3));
+        assert_eq!(super::distances(3, graph), HashMap::from([(3, 0)]));
+    }
+}
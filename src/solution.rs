@@ -0,0 +1,113 @@
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+
+use crate::input;
+use crate::params::Validate;
+use crate::parse::ParseError;
+
+/// Answer of a single puzzle part, independent of the integer type a day happens to use.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Int(i64),
+    UInt(u64),
+    Text(String),
+    Unimplemented,
+    /// The input is valid but has no answer for this part, for the reason given.
+    NoAnswer(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Int(v) => write!(f, "{v}"),
+            Answer::UInt(v) => write!(f, "{v}"),
+            Answer::Text(v) => write!(f, "{v}"),
+            Answer::Unimplemented => write!(f, "not implemented"),
+            Answer::NoAnswer(reason) => write!(f, "no answer: {reason}"),
+        }
+    }
+}
+
+macro_rules! answer_from {
+    ($variant:ident: $($ty:ty),*) => {
+        $(
+            impl From<$ty> for Answer {
+                fn from(value: $ty) -> Self {
+                    Answer::$variant(value as _)
+                }
+            }
+        )*
+    };
+}
+
+answer_from!(Int: i32, i64, isize);
+answer_from!(UInt: u32, u64, usize);
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Answer::Text(value.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn number(self) -> u8 {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+/// Common shape of every day: parse the puzzle input once, then solve both parts from it.
+pub trait Solution {
+    const DAY: u8;
+
+    type Input;
+
+    /// Numbers the puzzle fixes, such as a count of rounds, defaulting to the puzzle's values;
+    /// [`NoParams`](crate::params::NoParams) for a day without any.
+    type Params: Default + DeserializeOwned + Validate;
+
+    /// Parses text in the shape [`input::normalize`] gives it: no byte order mark, `\n` line
+    /// endings and one newline after the last line. Use [`Solution::read`] for raw file text.
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+
+    /// Parses puzzle text as it was read, normalizing it first.
+    fn read(input: &str) -> Result<Self::Input, ParseError> {
+        Self::parse(&input::normalize(input))
+    }
+
+    fn part1(input: &Self::Input, params: &Self::Params) -> Answer;
+
+    fn part2(_input: &Self::Input, _params: &Self::Params) -> Answer {
+        Answer::Unimplemented
+    }
+
+    fn solve(input: &Self::Input, params: &Self::Params, part: Part) -> Answer {
+        match part {
+            Part::One => Self::part1(input, params),
+            Part::Two => Self::part2(input, params),
+        }
+    }
+}
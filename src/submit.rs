@@ -0,0 +1,319 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use color_eyre::eyre::{bail, Result, WrapErr};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::http::Client;
+use crate::input::Source;
+use crate::params::Overrides;
+use crate::runner::{self, Outcome};
+use crate::solution::{Answer, Part};
+
+/// What the server made of a submitted answer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "verdict")]
+pub enum Response {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without a hint in which direction.
+    Wrong,
+    /// Submitted too soon after the previous answer, nothing was checked.
+    RateLimited {
+        wait_secs: u64,
+    },
+    /// The part was solved before, nothing was checked.
+    AlreadySolved,
+    /// A page none of the above matched, kept verbatim.
+    Unknown {
+        message: String,
+    },
+}
+
+impl Response {
+    /// Classifies the HTML page returned for an answer.
+    pub fn classify(page: &str) -> Self {
+        let article = Regex::new(r"(?s)<article[^>]*>(.*?)</article>").unwrap();
+        let tags = Regex::new(r"<[^>]*>").unwrap();
+        let message = article
+            .captures(page)
+            .map_or(page, |cap| cap.get(1).unwrap().as_str());
+        let message = tags.replace_all(message, "");
+        let message = message.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let wait = Regex::new(r"You have (?:(\d+)m )?(\d+)s left to wait").unwrap();
+        if message.contains("That's the right answer") {
+            Response::Correct
+        } else if message.contains("your answer is too high") {
+            Response::TooHigh
+        } else if message.contains("your answer is too low") {
+            Response::TooLow
+        } else if message.contains("That's not the right answer") {
+            Response::Wrong
+        } else if message.contains("You gave an answer too recently") {
+            let wait_secs = wait.captures(&message).map_or(0, |cap| {
+                let minutes = cap.get(1).map_or(0, |m| m.as_str().parse::<u64>().unwrap());
+                minutes * 60 + cap[2].parse::<u64>().unwrap()
+            });
+            Response::RateLimited { wait_secs }
+        } else if message.contains("You don't seem to be solving the right level") {
+            Response::AlreadySolved
+        } else {
+            Response::Unknown { message }
+        }
+    }
+
+    /// Whether the answer was actually checked, only those attempts must not be repeated.
+    fn is_checked(&self) -> bool {
+        matches!(
+            self,
+            Response::Correct | Response::TooHigh | Response::TooLow | Response::Wrong
+        )
+    }
+}
+
+impl fmt::Display for Response {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Response::Correct => write!(f, "correct"),
+            Response::TooHigh => write!(f, "wrong, too high"),
+            Response::TooLow => write!(f, "wrong, too low"),
+            Response::Wrong => write!(f, "wrong"),
+            Response::RateLimited { wait_secs } => {
+                write!(
+                    f,
+                    "rate limited, wait {:?}",
+                    Duration::from_secs(*wait_secs)
+                )
+            }
+            Response::AlreadySolved => write!(f, "already solved"),
+            Response::Unknown { message } => write!(f, "unrecognized response: {message}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attempt {
+    pub day: u8,
+    pub part: u8,
+    pub answer: String,
+    #[serde(flatten)]
+    pub response: Response,
+    /// Seconds since the Unix epoch.
+    pub at: u64,
+}
+
+/// Every answer ever submitted, kept as a JSON file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Log {
+    path: PathBuf,
+    pub attempts: Vec<Attempt>,
+}
+
+impl Log {
+    /// Reads the log at `path`, a missing file being an empty log.
+    pub fn load(path: &Path) -> Result<Self> {
+        let attempts = if path.exists() {
+            let log = fs::read_to_string(path)
+                .wrap_err_with(|| format!("could not read submission log {}", path.display()))?;
+            serde_json::from_str(&log)
+                .wrap_err_with(|| format!("invalid submission log {}", path.display()))?
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            attempts,
+        })
+    }
+
+    fn save(&self) -> Result<()> {
+        let log = serde_json::to_string_pretty(&self.attempts)?;
+        fs::write(&self.path, log + "\n")
+            .wrap_err_with(|| format!("could not write submission log {}", self.path.display()))
+    }
+
+    /// Why `answer` must not be submitted, if an earlier attempt already settles it.
+    fn refusal(&self, day: u8, part: Part, answer: &str) -> Option<String> {
+        let attempts = || {
+            self.attempts
+                .iter()
+                .filter(move |a| a.day == day && a.part == part.number() && a.response.is_checked())
+        };
+        if let Some(correct) = attempts().find(|a| a.response == Response::Correct) {
+            return Some(format!(
+                "day {day} part {part} is already solved with {}",
+                correct.answer
+            ));
+        }
+        attempts()
+            .find(|a| a.answer == answer)
+            .map(|a| format!("{answer} was already submitted, it was {}", a.response))
+    }
+
+    fn record(&mut self, day: u8, part: Part, answer: &str, response: &Response) -> Result<()> {
+        self.attempts.push(Attempt {
+            day,
+            part: part.number(),
+            answer: answer.to_string(),
+            response: response.clone(),
+            at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        });
+        self.save()
+    }
+}
+
+/// Solves one part of `day` and returns its answer as it would be submitted. The puzzle's own
+/// parameters are always used, whatever overrides are configured.
+pub fn answer(day: u8, source: &Source, part: Part) -> Result<String> {
+    match runner::run_day(day, source, &Overrides::default(), &[part], 1) {
+        Outcome::Solved(report) => match &report.parts[0].answer {
+            Answer::Unimplemented => bail!("day {day} part {part} is not implemented yet"),
+            Answer::NoAnswer(reason) => bail!("day {day} part {part} has no answer: {reason}"),
+            answer => Ok(answer.to_string()),
+        },
+        Outcome::Failed { error, .. } => bail!("day {day} failed: {error}"),
+        Outcome::Unimplemented { .. } => bail!("day {day} is not implemented yet"),
+    }
+}
+
+/// Posts `answer` unless the log shows it was checked before, and logs the response.
+pub fn submit(
+    day: u8,
+    part: Part,
+    answer: &str,
+    config: &Config,
+    state_dir: &Path,
+    log: &mut Log,
+) -> Result<Response> {
+    if let Some(reason) = log.refusal(day, part, answer) {
+        bail!("not submitting, {reason}");
+    }
+    let client = Client::new(config, state_dir)?;
+    let level = part.number().to_string();
+    let page = client.post_form(
+        &format!("/2022/day/{day}/answer"),
+        &[("level", &level), ("answer", answer)],
+    )?;
+    let response = Response::classify(&page);
+    log.record(day, part, answer, &response)?;
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::http::stub::Server;
+
+    fn page(message: &str) -> String {
+        format!("<html><main>\n<article><p>{message}</p></article>\n</main></html>")
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc-2022-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn responses_are_classified() {
+        let cases = [
+            (
+                "That's the right answer! You are <em>one gold star</em> closer.",
+                Response::Correct,
+            ),
+            (
+                "That's not the right answer; your answer is too high. Please wait one minute.",
+                Response::TooHigh,
+            ),
+            (
+                "That's not the right answer; your answer is too low.",
+                Response::TooLow,
+            ),
+            (
+                "That's not the right answer. If you're stuck, ...",
+                Response::Wrong,
+            ),
+            (
+                "You gave an answer too recently; you have to wait after submitting an answer \
+                 before trying again. You have 1m 5s left to wait.",
+                Response::RateLimited { wait_secs: 65 },
+            ),
+            (
+                "You gave an answer too recently. You have 42s left to wait.",
+                Response::RateLimited { wait_secs: 42 },
+            ),
+            (
+                "You don't seem to be solving the right level. Did you already complete it?",
+                Response::AlreadySolved,
+            ),
+            (
+                "Something <b>else</b>",
+                Response::Unknown {
+                    message: "Something else".to_string(),
+                },
+            ),
+        ];
+        for (message, expected) in cases {
+            assert_eq!(Response::classify(&page(message)), expected, "{message}");
+        }
+    }
+
+    #[test]
+    fn wrong_answer_is_submitted_once_and_logged() {
+        let server = Server::start(|_| {
+            (
+                200,
+                page("That's not the right answer; your answer is too low."),
+            )
+        });
+        let dir = temp_dir("submit-log");
+        let config = Config {
+            session: Some("abc".to_string()),
+            base_url: server.base_url.clone(),
+            min_request_interval: 0,
+            ..Config::default()
+        };
+        let log_path = dir.join("submissions.json");
+        let mut log = Log::load(&log_path).unwrap();
+
+        let response = submit(5, Part::Two, "CMZ", &config, &dir, &mut log).unwrap();
+        assert_eq!(response, Response::TooLow);
+        let requests = server.requests();
+        assert_eq!(
+            (requests[0].method.as_str(), requests[0].path.as_str()),
+            ("POST", "/2022/day/5/answer")
+        );
+        assert_eq!(requests[0].body, "level=2&answer=CMZ");
+
+        let mut log = Log::load(&log_path).unwrap();
+        assert_eq!(log.attempts.len(), 1);
+        let err = submit(5, Part::Two, "CMZ", &config, &dir, &mut log).unwrap_err();
+        assert!(err.to_string().contains("too low"), "{err}");
+        assert_eq!(server.requests().len(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rate_limited_attempt_can_be_retried() {
+        let mut log = Log::load(Path::new("unused.json")).unwrap();
+        log.attempts.push(Attempt {
+            day: 1,
+            part: 1,
+            answer: "7".to_string(),
+            response: Response::RateLimited { wait_secs: 30 },
+            at: 0,
+        });
+        assert_eq!(log.refusal(1, Part::One, "7"), None);
+        log.attempts[0].response = Response::Correct;
+        assert!(log.refusal(1, Part::One, "8").is_some());
+        assert_eq!(log.refusal(1, Part::Two, "7"), None);
+    }
+}
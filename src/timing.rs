@@ -0,0 +1,92 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::runner::DayReport;
+use crate::solution::Answer;
+
+pub fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let value = f();
+    (value, start.elapsed())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+}
+
+impl Stats {
+    pub fn new(durations: &[Duration]) -> Option<Self> {
+        if durations.is_empty() {
+            return None;
+        }
+        let mut sorted = durations.to_vec();
+        sorted.sort_unstable();
+        let mid = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] + sorted[mid]) / 2
+        } else {
+            sorted[mid]
+        };
+        let total: Duration = sorted.iter().sum();
+        Some(Self {
+            runs: sorted.len(),
+            min: sorted[0],
+            median,
+            mean: total / sorted.len() as u32,
+        })
+    }
+}
+
+/// Summary table of parse and part timings for every day that was run.
+pub struct Report<'a>(pub Vec<&'a DayReport>);
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>3} | {:<6} | {:>5} | {:>10} | {:>10} | {:>10}",
+            "Day", "Step", "Runs", "Min", "Median", "Mean"
+        )?;
+        writeln!(f, "{}", "-".repeat(60))?;
+        for report in &self.0 {
+            let steps = std::iter::once(("parse".to_string(), &report.parse)).chain(
+                report
+                    .parts
+                    .iter()
+                    .filter(|part| part.answer != Answer::Unimplemented)
+                    .map(|part| (format!("part {}", part.part), &part.durations)),
+            );
+            for (step, durations) in steps {
+                if let Some(stats) = Stats::new(durations) {
+                    writeln!(
+                        f,
+                        "{:>3} | {:<6} | {:>5} | {:>10.2?} | {:>10.2?} | {:>10.2?}",
+                        report.day, step, stats.runs, stats.min, stats.median, stats.mean
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn stats_of_odd_and_even_number_of_runs() {
+        let ms = Duration::from_millis;
+        let stats = Stats::new(&[ms(5), ms(1), ms(3)]).unwrap();
+        assert_eq!((stats.min, stats.median, stats.mean), (ms(1), ms(3), ms(3)));
+        let stats = Stats::new(&[ms(4), ms(1), ms(2), ms(9)]).unwrap();
+        assert_eq!((stats.min, stats.median, stats.mean), (ms(1), ms(3), ms(4)));
+        assert_eq!(Stats::new(&[]), None);
+    }
+}
@@ -0,0 +1,212 @@
+//! Typed events emitted by solvers while they run.
+//!
+//! Solvers never print. They [`emit`] events such as a finished round or a settled grain of sand,
+//! and subscribers decide what to do with them: log them, count them or [`record`] them for a
+//! visualization. An event is only built when someone listens, so a run without subscribers
+//! stays silent and pays next to nothing.
+//!
+//! Subscribers registered with [`subscribe`] belong to the current thread, which keeps parallel
+//! tests apart; the one installed with [`subscribe_globally`] hears every thread.
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::sync::OnceLock;
+
+use crate::geometry::Point;
+use crate::interval::IntervalSet;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// Day 10: the signal strength was sampled during `cycle`.
+    SignalSampled { cycle: i32, register: i32 },
+    /// Day 11: worry levels are kept modulo this product of every monkey's divisor.
+    WorryModulus { modulus: u64 },
+    /// Day 11: every monkey had its turn; `inspections` are their counts so far, by monkey.
+    RoundFinished { round: u32, inspections: Vec<u64> },
+    /// Day 14: a grain of sand came to rest.
+    SandSettled { at: Point },
+    /// Day 15: the positions of row `y` within reach of some sensor.
+    RowCovered { y: i64, covered: IntervalSet },
+    /// Day 17: rock number `rock`, counting from 0, came to rest on `cells`.
+    RockLanded {
+        rock: usize,
+        cells: Vec<Point>,
+        height: i64,
+    },
+}
+
+impl Event {
+    /// The day emitting this event.
+    pub fn day(&self) -> u8 {
+        match self {
+            Event::SignalSampled { .. } => 10,
+            Event::WorryModulus { .. } | Event::RoundFinished { .. } => 11,
+            Event::SandSettled { .. } => 14,
+            Event::RowCovered { .. } => 15,
+            Event::RockLanded { .. } => 17,
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::SignalSampled { cycle, register } => write!(
+                f,
+                "cycle {cycle}: X = {register}, signal strength {}",
+                cycle * register
+            ),
+            Event::WorryModulus { modulus } => write!(f, "worry levels kept modulo {modulus}"),
+            Event::RoundFinished { round, inspections } => {
+                write!(f, "round {round}: inspections {inspections:?}")
+            }
+            Event::SandSettled { at } => write!(f, "sand settled at {at:?}"),
+            Event::RowCovered { y, covered } => {
+                write!(f, "row {y}: covered {:?}", covered.intervals())
+            }
+            Event::RockLanded {
+                rock,
+                cells,
+                height,
+            } => write!(f, "rock {rock} landed on {cells:?}, tower height {height}"),
+        }
+    }
+}
+
+type Subscriber = Box<dyn FnMut(&Event)>;
+type GlobalSubscriber = Box<dyn Fn(&Event) + Send + Sync>;
+
+thread_local! {
+    static SUBSCRIBERS: RefCell<Vec<(u64, Subscriber)>> = const { RefCell::new(Vec::new()) };
+    static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+static GLOBAL: OnceLock<GlobalSubscriber> = OnceLock::new();
+
+/// Hands the event built by `event` to every subscriber, building it only when there is one.
+/// Events emitted from within a subscriber are dropped.
+pub fn emit(event: impl FnOnce() -> Event) {
+    let global = GLOBAL.get();
+    SUBSCRIBERS.with(|subscribers| {
+        let Ok(mut subscribers) = subscribers.try_borrow_mut() else {
+            return;
+        };
+        if subscribers.is_empty() && global.is_none() {
+            return;
+        }
+        let event = event();
+        for (_, subscriber) in subscribers.iter_mut() {
+            subscriber(&event);
+        }
+        if let Some(global) = global {
+            global(&event);
+        }
+    })
+}
+
+/// Keeps a subscriber of the current thread registered until it is dropped.
+#[must_use = "the subscriber is removed as soon as the subscription is dropped"]
+pub struct Subscription {
+    id: u64,
+    // Registered on, and so only removable from, the current thread.
+    _thread: PhantomData<*const ()>,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        SUBSCRIBERS.with(|subscribers| {
+            if let Ok(mut subscribers) = subscribers.try_borrow_mut() {
+                subscribers.retain(|(id, _)| *id != self.id);
+            }
+        })
+    }
+}
+
+/// Calls `subscriber` with every event emitted on the current thread while the returned
+/// subscription lives.
+pub fn subscribe(subscriber: impl FnMut(&Event) + 'static) -> Subscription {
+    let id = NEXT_ID.with(|next| next.replace(next.get() + 1));
+    SUBSCRIBERS.with(|subscribers| subscribers.borrow_mut().push((id, Box::new(subscriber))));
+    Subscription {
+        id,
+        _thread: PhantomData,
+    }
+}
+
+/// Calls `subscriber` with every event emitted on any thread, for the rest of the process. Only
+/// the first global subscriber is installed; returns whether this one was.
+pub fn subscribe_globally(subscriber: impl Fn(&Event) + Send + Sync + 'static) -> bool {
+    GLOBAL.set(Box::new(subscriber)).is_ok()
+}
+
+/// Runs `run` and returns its result along with the events it emitted, in order.
+pub fn record<R>(run: impl FnOnce() -> R) -> (R, Vec<Event>) {
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let sink = Rc::clone(&events);
+    let subscription = subscribe(move |event| sink.borrow_mut().push(event.clone()));
+    let result = run();
+    drop(subscription);
+    (result, events.take())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn settle(x: i64) {
+        emit(|| Event::SandSettled {
+            at: Point::new(x, 0),
+        });
+    }
+
+    #[test]
+    fn recording_collects_events_in_order() {
+        let (answer, events) = record(|| {
+            settle(1);
+            settle(2);
+            42
+        });
+        assert_eq!(answer, 42);
+        assert_eq!(
+            events,
+            [
+                Event::SandSettled {
+                    at: Point::new(1, 0)
+                },
+                Event::SandSettled {
+                    at: Point::new(2, 0)
+                }
+            ]
+        );
+        assert_eq!(events[0].to_string(), "sand settled at (1, 0)");
+        assert_eq!(events[0].day(), 14);
+    }
+
+    #[test]
+    fn events_are_only_built_for_subscribers() {
+        let built = Rc::new(Cell::new(0));
+        let emit_counted = || {
+            let built = Rc::clone(&built);
+            emit(move || {
+                built.set(built.get() + 1);
+                Event::WorryModulus { modulus: 6 }
+            })
+        };
+        emit_counted();
+        assert_eq!(built.get(), 0);
+
+        let seen = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&seen);
+        let subscription = subscribe(move |_| counter.set(counter.get() + 1));
+        let (_, recorded) = record(emit_counted);
+        assert_eq!((built.get(), seen.get(), recorded.len()), (1, 1, 1));
+
+        drop(subscription);
+        emit_counted();
+        assert_eq!((built.get(), seen.get()), (1, 1));
+    }
+}
@@ -0,0 +1,290 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::eyre::{Result, WrapErr};
+use serde::Deserialize;
+
+use crate::input::{self, Source};
+use crate::runner::{self, DayReport};
+use crate::solution::{Answer, Part};
+
+/// Expected answer as written in the manifest, either a number or a string.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Expected {
+    Int(i64),
+    Text(String),
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Int(v) => write!(f, "{v}"),
+            Expected::Text(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Entry {
+    pub day: u8,
+    pub path: PathBuf,
+    pub part1: Option<Expected>,
+    pub part2: Option<Expected>,
+}
+
+impl Entry {
+    fn expected(&self, part: Part) -> Option<&Expected> {
+        match part {
+            Part::One => self.part1.as_ref(),
+            Part::Two => self.part2.as_ref(),
+        }
+    }
+}
+
+/// Known answers, one entry per input file.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+pub struct Manifest {
+    #[serde(default, rename = "input")]
+    pub inputs: Vec<Entry>,
+    /// Directory the entry paths are relative to.
+    #[serde(skip)]
+    pub base_dir: PathBuf,
+}
+
+impl Manifest {
+    pub fn parse(manifest: &str, base_dir: &Path) -> Result<Self> {
+        let mut manifest: Manifest = toml::from_str(manifest)?;
+        manifest.base_dir = base_dir.to_path_buf();
+        Ok(manifest)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let manifest = fs::read_to_string(path)
+            .wrap_err_with(|| format!("could not read manifest {}", path.display()))?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        Self::parse(&manifest, base_dir)
+            .wrap_err_with(|| format!("invalid manifest {}", path.display()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: String, actual: String },
+    Missing,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub day: u8,
+    pub part: Part,
+    /// Input the check was run on, `None` when the day has no manifest entry at all.
+    pub path: Option<PathBuf>,
+    pub verdict: Verdict,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self
+            .path
+            .as_ref()
+            .map_or("-".to_string(), |p| p.display().to_string());
+        write!(f, "Day {} part {} ({path}): ", self.day, self.part)?;
+        match &self.verdict {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::Fail { expected, actual } => {
+                write!(f, "FAIL, expected {expected}, got {actual}")
+            }
+            Verdict::Missing => write!(f, "missing"),
+        }
+    }
+}
+
+fn check_entry(entry: &Entry, base_dir: &Path) -> Vec<Check> {
+    let path = base_dir.join(&entry.path);
+    let solved = match runner::find(entry.day) {
+        Some(solver) => runner::catch_panic(|| {
+            input::load(entry.day, &Source::Path(path), None)
+                .and_then(|input| solver.solve(&input, &toml::Table::new(), &Part::ALL, 1))
+                .map_err(|err| format!("error: {err:#}"))
+        })
+        .and_then(|solved| solved),
+        None => Err("not implemented".to_string()),
+    };
+    judge(entry, solved)
+}
+
+/// Checks every part of `entry` against the report of its input, or fails every part it expects
+/// an answer for with the error that kept the input from being solved.
+fn judge(entry: &Entry, solved: Result<DayReport, String>) -> Vec<Check> {
+    let check = |part: Part, verdict: Verdict| Check {
+        day: entry.day,
+        part,
+        path: Some(entry.path.clone()),
+        verdict,
+    };
+    let report = match solved {
+        Ok(report) => report,
+        Err(actual) => {
+            return Part::ALL
+                .iter()
+                .map(|&part| match entry.expected(part) {
+                    Some(expected) => check(
+                        part,
+                        Verdict::Fail {
+                            expected: expected.to_string(),
+                            actual: actual.clone(),
+                        },
+                    ),
+                    None => check(part, Verdict::Missing),
+                })
+                .collect()
+        }
+    };
+    report
+        .parts
+        .iter()
+        .map(|part| {
+            let verdict = match entry.expected(part.part) {
+                None => Verdict::Missing,
+                Some(expected)
+                    if part.answer != Answer::Unimplemented
+                        && expected.to_string() == part.answer.to_string() =>
+                {
+                    Verdict::Pass
+                }
+                Some(expected) => Verdict::Fail {
+                    expected: expected.to_string(),
+                    actual: part.answer.to_string(),
+                },
+            };
+            check(part.part, verdict)
+        })
+        .collect()
+}
+
+/// Checks every manifest entry of the given days; days without any entry are reported missing.
+pub fn verify(manifest: &Manifest, days: &[u8]) -> Vec<Check> {
+    let mut checks = Vec::new();
+    for &day in days {
+        let entries: Vec<&Entry> = manifest.inputs.iter().filter(|e| e.day == day).collect();
+        if entries.is_empty() {
+            checks.extend(Part::ALL.iter().map(|&part| Check {
+                day,
+                part,
+                path: None,
+                verdict: Verdict::Missing,
+            }));
+        }
+        for entry in entries {
+            checks.extend(check_entry(entry, &manifest.base_dir));
+        }
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn manifest(toml: &str) -> Manifest {
+        Manifest::parse(toml, Path::new(env!("CARGO_MANIFEST_DIR"))).unwrap()
+    }
+
+    fn verdicts(checks: &[Check]) -> Vec<(u8, Part, Verdict)> {
+        checks
+            .iter()
+            .map(|c| (c.day, c.part, c.verdict.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn verify_reports_pass_fail_and_missing() {
+        let manifest = manifest(
+            r#"
+            [[input]]
+            day = 1
+            path = "src/day_1/test_input.txt"
+            part1 = 24000
+            part2 = 1
+
+            [[input]]
+            day = 5
+            path = "src/day_5/test_input.txt"
+            part1 = "CMZ"
+            "#,
+        );
+        assert_eq!(
+            verdicts(&verify(&manifest, &[1, 5, 6])),
+            vec![
+                (1, Part::One, Verdict::Pass),
+                (
+                    1,
+                    Part::Two,
+                    Verdict::Fail {
+                        expected: "1".to_string(),
+                        actual: "45000".to_string()
+                    }
+                ),
+                (5, Part::One, Verdict::Pass),
+                (5, Part::Two, Verdict::Missing),
+                (6, Part::One, Verdict::Missing),
+                (6, Part::Two, Verdict::Missing),
+            ]
+        );
+    }
+
+    #[test]
+    fn unreadable_input_fails_every_expected_part() {
+        let manifest = manifest(
+            r#"
+            [[input]]
+            day = 1
+            path = "does/not/exist.txt"
+            part1 = 24000
+            "#,
+        );
+        let checks = verify(&manifest, &[1]);
+        assert!(matches!(checks[0].verdict, Verdict::Fail { .. }));
+        assert_eq!(checks[1].verdict, Verdict::Missing);
+    }
+
+    #[test]
+    fn panicking_day_fails_every_expected_part() {
+        let entry = Entry {
+            day: 1,
+            path: PathBuf::from("src/day_1/test_input.txt"),
+            part1: Some(Expected::Int(24000)),
+            part2: None,
+        };
+        let checks = judge(&entry, runner::catch_panic(|| panic!("boom")));
+        assert_eq!(
+            verdicts(&checks),
+            vec![
+                (
+                    1,
+                    Part::One,
+                    Verdict::Fail {
+                        expected: "24000".to_string(),
+                        actual: "panicked: boom".to_string()
+                    }
+                ),
+                (1, Part::Two, Verdict::Missing),
+            ]
+        );
+    }
+
+    #[test]
+    fn shipped_manifest_parses() {
+        let manifest =
+            Manifest::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("answers.toml")).unwrap();
+        assert!(manifest
+            .inputs
+            .iter()
+            .all(|e| e.part1.is_some() || e.part2.is_some()));
+    }
+}
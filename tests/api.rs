@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+
+use pretty_assertions::assert_eq;
+
+use aoc_2022_rust::day_1::day1::Day1;
+use aoc_2022_rust::day_5::day5::Day5;
+use aoc_2022_rust::day_8::day8::Day8;
+use aoc_2022_rust::geometry::Point;
+use aoc_2022_rust::input::Source;
+use aoc_2022_rust::params::{NoParams, Overrides};
+use aoc_2022_rust::runner::{self, Outcome};
+use aoc_2022_rust::solution::{Answer, Part, Solution};
+
+fn example(day: u8) -> PathBuf {
+    [
+        env!("CARGO_MANIFEST_DIR"),
+        "src",
+        &format!("day_{day}"),
+        "test_input.txt",
+    ]
+    .iter()
+    .collect()
+}
+
+#[test]
+fn days_are_solved_through_the_solution_trait() {
+    let calories = Day1::parse(include_str!("../src/day_1/test_input.txt")).unwrap();
+    assert_eq!(Day1::part1(&calories, &NoParams), Answer::UInt(24000));
+    assert_eq!(Day1::part2(&calories, &NoParams), Answer::UInt(45000));
+
+    let procedure = Day5::parse(include_str!("../src/day_5/test_input.txt")).unwrap();
+    assert_eq!(procedure.crates.len(), 3);
+    assert_eq!(procedure.steps.len(), 4);
+    assert_eq!(Day5::part1(&procedure, &NoParams), Answer::from("CMZ"));
+
+    let procedure = Day5::read("\u{feff}[A] [B]\r\n 1   2 \r\n\r\n").unwrap();
+    assert!(procedure.steps.is_empty());
+    assert_eq!(Day5::part2(&procedure, &NoParams), Answer::from("AB"));
+    let err = Day5::read("\u{feff}[A] [B] [C]\r\n 1   2 \r\n").unwrap_err();
+    assert_eq!((err.line, err.column, err.text.as_str()), (1, 10, "C"));
+}
+
+#[test]
+fn parsed_models_are_usable_on_their_own() {
+    let grid = Day8::parse(include_str!("../src/day_8/test_input.txt")).unwrap();
+    assert_eq!((grid.width(), grid.height()), (5, 5));
+    assert_eq!(grid.cell(Point::new(2, 1)), Some(&5));
+    assert_eq!(grid.cell(Point::new(5, 0)), None);
+
+    let sensor = Point::new(8, 7);
+    assert_eq!(sensor.manhattan(Point::new(2, 10)), 9);
+}
+
+#[test]
+fn parse_errors_point_at_the_input() {
+    let err = Day1::parse("100\n\nabc\n").unwrap_err();
+    assert_eq!((err.day, err.line, err.column), (1, 3, 1));
+}
+
+#[test]
+fn runner_solves_days_by_number() {
+    let outcome = runner::run_day(
+        1,
+        &Source::Path(example(1)),
+        &Overrides::default(),
+        &Part::ALL,
+        1,
+    );
+    let Outcome::Solved(report) = outcome else {
+        panic!("day 1 was not solved");
+    };
+    let answers: Vec<String> = report.parts.iter().map(|p| p.answer.to_string()).collect();
+    assert_eq!(answers, ["24000", "45000"]);
+
+    assert!(matches!(
+        runner::run_day(
+            25,
+            &Source::Path(example(1)),
+            &Overrides::default(),
+            &Part::ALL,
+            1
+        ),
+        Outcome::Unimplemented { day: 25 }
+    ));
+}